@@ -0,0 +1,16 @@
+//! Feeds arbitrary source text through the front end, which must never panic
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use elgin::errors::Diagnostics;
+use elgin::source_map::SourceMap;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(source) = std::str::from_utf8(data) {
+        let mut sources = SourceMap::new();
+        let file = sources.add("fuzz.eln", source.to_owned());
+        let diagnostics = Diagnostics::new();
+        let _ = elgin::check(&sources, file, &diagnostics);
+    }
+});
@@ -3,7 +3,7 @@
 
 use crate::ir::*;
 use crate::types::Type;
-use crate::errors::Span;
+use crate::errors::{Error, ErrorType, Span};
 
 use std::collections::HashMap;
 
@@ -21,12 +21,14 @@ impl<'i> IRBuilder<'i> {
                 scope.insert(self.procs[index].args[i].clone(), arg_type.clone());
             }
             let proc = self.procs[index].clone();
+            self.file = proc.file;
             let mut constraints = self.gen_constraints(&proc)?;
             add_literal_constaints(&mut constraints, &mut self.procs);
             new_procs.push(self.solve_constraints(&proc, &constraints)?);
+            self.scopes.pop();
             index += 1;
         }
-        self.procs = dbg!(new_procs);
+        self.procs = new_procs;
         Some(())
     }
 
@@ -40,89 +42,111 @@ impl<'i> IRBuilder<'i> {
                     stack.push(ins.contents.typ.clone());
                 }
                 Load(var) => {
-                    stack.push(self.locate_var(&var)?);
+                    stack.push(self.locate_var(&var, ins.pos)?);
                 }
                 Store(var) => {
-                    let typ = stack.pop().unwrap();
-                    self.add_constraint(&mut constraints, ins.contents.typ.clone(), typ);
-                    self.add_constraint(&mut constraints, ins.contents.typ.clone(), self.locate_var(&var)?);
+                    let typ = self.pop(&mut stack, ins)?;
+                    self.add_constraint(&mut constraints, ins, ins.contents.typ.clone(), typ)?;
+                    self.add_constraint(&mut constraints, ins, ins.contents.typ.clone(), self.locate_var(&var, ins.pos)?)?;
                 }
                 StoreIndexed(var) => {
-                    let _index_type = stack.pop().unwrap();
-                    let value_type = stack.pop().unwrap();
-                    if let Type::Array(_, t) = self.locate_var(&var)? {
-                        self.add_constraint(&mut constraints, *t, value_type);
+                    let _index_type = self.pop(&mut stack, ins)?;
+                    let value_type = self.pop(&mut stack, ins)?;
+                    if let Type::Array(_, t) = self.locate_var(&var, ins.pos)? {
+                        self.add_constraint(&mut constraints, ins, *t, value_type)?;
                     }
                     // TODO what happens here?
                 }
                 Allocate(var) => {
-                    let content_type = stack.pop().unwrap();
+                    let content_type = self.pop(&mut stack, ins)?;
                     let var_type = ins.contents.typ.clone();
                     let scope_index = self.scopes.len() - 1;
                     self.scopes[scope_index].insert(var, var_type.clone());
-                    self.add_constraint(&mut constraints, var_type, content_type);
+                    self.add_constraint(&mut constraints, ins, var_type, content_type)?;
                 }
                 Index => {
-                    let _index_type = stack.pop().unwrap();
-                    let object_type = stack.pop().unwrap();
+                    let _index_type = self.pop(&mut stack, ins)?;
+                    let object_type = self.pop(&mut stack, ins)?;
                     if let Type::Array(_, t) = object_type {
+                        self.add_constraint(&mut constraints, ins, ins.contents.typ.clone(), (*t).clone())?;
                         stack.push(*t);
                     } else {
-                        panic!();
+                        self.diagnostics.type_error(
+                            "E0016",
+                            format!("Can't index into a value of type {:?}", object_type).as_str(),
+                            ins.file,
+                            ins.pos,
+                            ins.len,
+                        );
+                        return None;
                     }
                 }
 
                 Branch(_, _) => {
                     self.add_constraint(
                         &mut constraints,
-                        stack.pop().unwrap(),
+                        ins,
+                        self.pop(&mut stack, ins)?,
                         Type::Bool,
-                    );
+                    )?;
                 }
                 Jump(_) => (),
                 Label(_) => (),
 
                 Call(proc_name) => {
-                    let proc = self.locate_proc(&proc_name)?.clone();
-                    //let arg_count = proc.arg_types.len();
+                    let proc = self.locate_proc(&proc_name, ins.pos)?.clone();
+                    if stack.len() < proc.args.len() {
+                        self.diagnostics.internal_error("Not enough arguments on the type stack", ins.file, ins.pos, ins.len);
+                        return None;
+                    }
                     {
                         let args = &stack[stack.len() - proc.args.len()..];
                         for (i, arg) in args.iter().enumerate() {
-                            self.add_constraint(&mut constraints, arg.clone(), proc.arg_types[i].clone());
+                            self.add_constraint(&mut constraints, ins, arg.clone(), proc.arg_types[i].clone())?;
                         }
                     }
                     stack.truncate(stack.len() - proc.args.len());
-                    stack.push(proc.ret_type.clone());
+                    if proc.ret_type == Type::Undefined {
+                        // there's no value, which `pop` reports if anything tries to use it
+                        stack.push(Type::NoReturn);
+                    } else {
+                        stack.push(proc.ret_type.clone());
+                    }
                 }
                 Return => {
-                    let type_to_return = stack.pop().unwrap();
-                    //let ret_type = ins.typ.clone();
-                    self.add_constraint(&mut constraints, type_to_return, proc.ret_type.clone());
+                    let type_to_return = self.pop(&mut stack, ins)?;
+                    if (type_to_return == Type::Undefined) != (proc.ret_type == Type::Undefined) {
+                        self.mismatched_return(proc, ins);
+                        return None;
+                    }
+                    self.add_constraint(&mut constraints, ins, type_to_return, proc.ret_type.clone())?;
                 }
 
                 Negate(_) => {
-                    let t1 = stack.pop().unwrap();
-                    self.add_constraint(&mut constraints, t1.clone(), ins.contents.typ.clone());
+                    let t1 = self.pop(&mut stack, ins)?;
+                    self.add_constraint(&mut constraints, ins, t1.clone(), ins.contents.typ.clone())?;
+                    stack.push(ins.contents.typ.clone());
                 }
                 // TODO more specific constraints???
                 Add(_) | Subtract(_) | Multiply(_) | IntDivide | Divide => {
-                    let t1 = stack.pop().unwrap();
-                    let t2 = stack.pop().unwrap();
-                    self.add_constraint(&mut constraints, t1.clone(), t2.clone());
-                    self.add_constraint(&mut constraints, t1.clone(), ins.contents.typ.clone());
-                    self.add_constraint(&mut constraints, t2.clone(), ins.contents.typ.clone());
+                    let t1 = self.pop(&mut stack, ins)?;
+                    let t2 = self.pop(&mut stack, ins)?;
+                    self.add_constraint(&mut constraints, ins, t1.clone(), t2.clone())?;
+                    self.add_constraint(&mut constraints, ins, t1.clone(), ins.contents.typ.clone())?;
+                    self.add_constraint(&mut constraints, ins, t2.clone(), ins.contents.typ.clone())?;
                     stack.push(ins.contents.typ.clone());
                 }
 
                 Compare(_) => {
-                    let t1 = stack.pop().unwrap();
-                    let t2 = stack.pop().unwrap();
-                    self.add_constraint(&mut constraints, t1.clone(), t2.clone());
+                    let t1 = self.pop(&mut stack, ins)?;
+                    let t2 = self.pop(&mut stack, ins)?;
+                    self.add_constraint(&mut constraints, ins, t1.clone(), t2.clone())?;
                     self.add_constraint(
                         &mut constraints,
+                        ins,
                         ins.contents.typ.clone(),
                         Type::Bool,
-                    );
+                    )?;
                     stack.push(Type::Bool);
                 }
             };
@@ -130,49 +154,207 @@ impl<'i> IRBuilder<'i> {
         Some(constraints)
     }
 
+    /// Pops the type of the value an instruction consumes
+    fn pop(&self, stack: &mut Vec<Type>, ins: &Span<Instruction>) -> Option<Type> {
+        let typ = stack.pop();
+        match typ {
+            None => self.diagnostics.internal_error("The type stack is empty", ins.file, ins.pos, ins.len),
+            Some(Type::NoReturn) => {
+                self.diagnostics.emit(
+                    Error::new(
+                        ErrorType::TypeError,
+                        "This uses the value of a call to a proc which doesn't return one",
+                        ins.file,
+                        ins.pos,
+                        ins.len,
+                    )
+                    .code("E0024")
+                    .help("give the proc a return type, such as `: i32`"),
+                );
+                return None;
+            }
+            Some(_) => (),
+        }
+        typ
+    }
+
+    /// Reports a `return` which doesn't match whether `proc` has a return type
+    fn mismatched_return(&self, proc: &IRProc, ins: &Span<Instruction>) {
+        let (msg, help) = if proc.ret_type == Type::Undefined {
+            (
+                format!("{} has no return type, so it can't return a value", proc.name),
+                "give it a return type, such as `: i32`".to_owned(),
+            )
+        } else {
+            (
+                format!("{} has to return a {:?}", proc.name, proc.ret_type),
+                "return a value, such as `return 0`".to_owned(),
+            )
+        };
+        self.diagnostics.emit(
+            Error::new(ErrorType::TypeError, msg.as_str(), ins.file, ins.pos, ins.len)
+                .code("E0024")
+                .help(help.as_str()),
+        );
+    }
+
     fn solve_constraints(&self, proc: &IRProc, constraints: &Constraints) -> Option<IRProc> {
-        println!("Generated constraints:");
-        for (t1, t2) in constraints {
-            println!("{:?} == {:?}", t1, t2);
+        if self.verbose {
+            eprintln!("Generated constraints for {}:", proc.name);
+            for (t1, t2) in constraints {
+                eprintln!("{:?} == {:?}", t1, t2);
+            }
+            eprintln!("------------------------");
         }
-        println!("------------------------");
         let mut new_body = proc.body.clone();
         let mut new_constraints = constraints.clone();
 
-        //while new_constraints.len() > 0 {
-        for _ in 1..4 {
-            for (t1, t2) in constraints {
+        // each rule is applied to the ones after it, so a chain such as $1 == $2, $2 == intLiteral
+        // and intLiteral == i32 ends up at i32, whatever order the rules came in
+        for i in 0..new_constraints.len() {
+            let (t1, t2) = match new_constraints[i].clone() {
+                (t1, t2) if !t1.is_resolved() => (t1, t2),
+                (t1, t2) if !t2.is_resolved() => (t2, t1),
+                // two known types are already as solved as they get
+                _ => continue,
+            };
+            if t1 != t2 {
                 // set t1 == t2
-                new_body = substitute_proc_body(new_body, t1, t2); // replace in the proc
-                new_constraints = substitute_constraints(&new_constraints, t1, t2);
+                new_body = substitute_proc_body(new_body, &t1, &t2); // replace in the proc
+                new_constraints = substitute_constraints(&new_constraints, &t1, &t2);
                 // replace in the rules
             }
         }
 
+        // codegen needs to know every type, so anything left unknown is reported here
+        let unresolved = new_body
+            .iter()
+            .position(|ins| matches!(ins.contents.ins, InstructionType::Allocate(_)) && !ins.contents.typ.is_resolved())
+            .or_else(|| new_body.iter().position(|ins| !ins.contents.typ.is_resolved()));
+        if let Some(index) = unresolved {
+            let ins = &new_body[index];
+            let (msg, help) = match &ins.contents.ins {
+                InstructionType::Allocate(var) => {
+                    // the suggestion goes by the value, which is the instruction before
+                    let value = index.checked_sub(1).map(|i| (&proc.body[i].contents.typ, &new_body[i].contents.typ));
+                    let typ = match value {
+                        Some((_, solved)) if solved.is_resolved() && *solved != Type::Undefined => format!("{:?}", solved),
+                        Some((original, _)) if original.is_float() => "f64".to_owned(),
+                        _ => "i32".to_owned(),
+                    };
+                    (
+                        format!("Couldn't infer the type of {}", var),
+                        format!("give it a type, such as `var {}: {}`", var, typ),
+                    )
+                }
+                _ => (
+                    "Couldn't infer the type of this expression".to_owned(),
+                    "give the variables it uses explicit types, or a literal a suffix such as `1i32`".to_owned(),
+                ),
+            };
+            self.diagnostics.emit(
+                Error::new(ErrorType::TypeError, msg.as_str(), ins.file, ins.pos, ins.len)
+                    .code("E0023")
+                    .help(help.as_str()),
+            );
+            return None;
+        }
+
+        for ins in &new_body {
+            let typ = &ins.contents.typ;
+            let (op, allowed) = match ins.contents.ins {
+                InstructionType::Negate(_) => ("-", typ.is_integer() || typ.is_float()),
+                InstructionType::Add(_) => ("+", typ.is_integer() || typ.is_float()),
+                InstructionType::Subtract(_) => ("-", typ.is_integer() || typ.is_float()),
+                InstructionType::Multiply(_) => ("*", typ.is_integer() || typ.is_float()),
+                InstructionType::IntDivide => ("//", typ.is_integer()),
+                InstructionType::Divide => ("/", typ.is_float()),
+                _ => continue,
+            };
+            if !allowed {
+                let mut error = Error::new(
+                    ErrorType::TypeError,
+                    format!("Can't use `{}` on values of type {:?}", op, typ).as_str(),
+                    ins.file,
+                    ins.pos,
+                    ins.len,
+                )
+                .code("E0025");
+                if typ.is_integer() {
+                    error = error.help("use `//` to divide integers");
+                } else if typ.is_float() {
+                    error = error.help("use `/` to divide floats");
+                }
+                self.diagnostics.emit(error);
+                return None;
+            }
+        }
+
+        if self.verbose {
+            for (old, new) in proc.body.iter().zip(&new_body) {
+                if let (InstructionType::Allocate(var), Type::Variable(_)) = (&old.contents.ins, &old.contents.typ) {
+                    self.diagnostics.note(
+                        ErrorType::TypeInference,
+                        format!("Inferred the type of {} to be {:?}", var, new.contents.typ).as_str(),
+                        new.file,
+                        new.pos,
+                        new.len,
+                    );
+                }
+            }
+        }
+
         Some(IRProc {
             name: proc.name.clone(),
             args: proc.args.clone(),
             arg_types: proc.arg_types.clone(),
             ret_type: proc.ret_type.clone(),
             body: new_body,
+            file: proc.file,
+            pos: proc.pos,
+            len: proc.len,
         })
     }
 
 
-    fn add_constraint(&mut self, constraints: &mut Constraints, t1: Type, t2: Type) {
-        println!("Trying to add constraint: {:?} == {:?}", t1.clone(), t2.clone());
-        // TODO Some of these constraints just shouldn't be permitted at all and should raise a type
-        // error. For example, you shouldn't be able to add a constraint i8 == f64
+    fn add_constraint(&mut self, constraints: &mut Constraints, ins: &Span<Instruction>, t1: Type, t2: Type) -> Option<()> {
+        if self.verbose {
+            eprintln!("Trying to add constraint: {:?} == {:?}", t1.clone(), t2.clone());
+        }
         if t1 == t2 {
-            return;
+            return Some(());
         }
         if t1 == Type::StrLiteral || t2 == Type::StrLiteral {
-            return;
+            // a string literal is a pointer to its first byte
+            match (t1, t2) {
+                (var @ Type::Variable(_), _) | (_, var @ Type::Variable(_)) => {
+                    constraints.push((var, Type::Ptr(Box::new(Type::I8))));
+                }
+                _ => (),
+            }
+            return Some(());
         }
         if t1 == Type::Undefined || t2 == Type::Undefined {
-            return;
+            return Some(());
+        }
+        let mismatched = match (&t1, &t2) {
+            (Type::IntLiteral, t) | (t, Type::IntLiteral) => t.is_resolved() && !t.is_integer(),
+            (Type::FloatLiteral, t) | (t, Type::FloatLiteral) => t.is_resolved() && !t.is_float(),
+            _ => t1.is_resolved() && t2.is_resolved(),
+        };
+        if mismatched {
+            self.diagnostics.type_error(
+                "E0025",
+                format!("Mismatched types {} and {}", describe(&t1), describe(&t2)).as_str(),
+                ins.file,
+                ins.pos,
+                ins.len,
+            );
+            return None;
+        }
+        if self.verbose {
+            eprintln!("After transformation: {:?} == {:?}", t1.clone(), t2.clone());
         }
-        println!("After transformation: {:?} == {:?}", t1.clone(), t2.clone());
         if let Type::Variable(_) = t2 {
             constraints.push((t2, t1));
         } else {
@@ -184,6 +366,16 @@ impl<'i> IRBuilder<'i> {
                 constraints.push((t1, t2));
             }
         }
+        Some(())
+    }
+}
+
+/// Names a type for an error, saying what a literal is rather than its placeholder type
+fn describe(typ: &Type) -> String {
+    match typ {
+        Type::IntLiteral => "integer literal".to_owned(),
+        Type::FloatLiteral => "float literal".to_owned(),
+        t => format!("{:?}", t),
     }
 }
 
@@ -191,16 +383,19 @@ fn substitute_proc_body(body: Vec<Span<Instruction>>, t1: &Type, t2: &Type) -> V
     let mut new_body = vec![];
 
     for ins in body {
-        new_body.push(spanned(Instruction {
-            ins: ins.contents.ins,
-            typ: if ins.contents.typ.clone() == t1.clone() {
-                t2.clone()
-            //} else if ins.typ.clone() == t2.clone() {
-            //    t1.clone()
-            } else {
-                ins.contents.typ
+        new_body.push(Span {
+            contents: Instruction {
+                ins: ins.contents.ins,
+                typ: if ins.contents.typ.clone() == t1.clone() {
+                    t2.clone()
+                //} else if ins.typ.clone() == t2.clone() {
+                //    t1.clone()
+                } else {
+                    ins.contents.typ
+                },
             },
-        }, ins.pos, ins.len));
+            ..ins
+        });
     }
     new_body
 }
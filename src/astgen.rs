@@ -2,9 +2,15 @@
 
 use crate::parser::Parser;
 use crate::lexer::Token;
-use crate::errors::{Logger, Span};
+use crate::errors::{Error, ErrorType, ErrorType::SyntaxError, Span};
+use crate::lints::UNUSED_DOC_COMMENTS;
 use crate::types::Type;
 
+use std::mem;
+
+/// How deeply expressions and blocks can be nested
+const MAX_DEPTH: usize = 128;
+
 #[derive(Debug, Clone)]
 pub enum Node {
     Literal {
@@ -56,6 +62,7 @@ pub enum Node {
         name: String,
         typ: Type,
         value: Box<Span<Node>>,
+        docs: String,
     },
     AssignStatement {
         name: String,
@@ -72,6 +79,7 @@ pub enum Node {
         arg_types: Vec<Type>,
         ret_type: Type,
         body: Box<Span<Node>>,
+        docs: String,
     },
     ReturnStatement {
         val: Box<Span<Node>>,
@@ -81,31 +89,69 @@ pub enum Node {
     },
     BreakStatement,
     ContinueStatement,
+    // stands in for a statement which couldn't be parsed
+    Error,
 }
 
-fn spanned(node: Node, pos: usize, len: usize) -> Span<Node> {
-    Span {
-        contents: node.clone(),
-        pos,
-        len,
+impl Node {
+    /// The nodes directly inside this one, in source order
+    pub fn children(&self) -> Vec<&Span<Node>> {
+        match self {
+            Node::Call { args, .. } => args.iter().collect(),
+            Node::InfixOp { left, right, .. } => vec![left, right],
+            Node::PrefixOp { right, .. } => vec![right],
+            Node::PostfixOp { left, .. } => vec![left],
+            Node::IndexOp { object, index } => vec![object, index],
+            Node::IfStatement { condition, body, else_body } => vec![condition, body, else_body],
+            Node::WhileStatement { condition, body } => vec![condition, body],
+            Node::Block { nodes } => nodes.iter().collect(),
+            Node::VarStatement { value, .. }
+            | Node::ConstStatement { value, .. }
+            | Node::AssignStatement { value, .. }
+            | Node::ReturnStatement { val: value } => vec![value],
+            Node::IndexedAssignStatement { index, value, .. } => vec![index, value],
+            Node::ProcStatement { body, .. } => vec![body],
+            Node::Literal { .. } | Node::VariableRef { .. } | Node::UseStatement { .. } => vec![],
+            Node::BreakStatement | Node::ContinueStatement | Node::Error => vec![],
+        }
     }
 }
 
 
 impl<'p> Parser<'p> {
+    fn spanned(&self, node: Node, pos: usize, len: usize) -> Span<Node> {
+        Span {
+            contents: node,
+            file: self.file,
+            pos,
+            len,
+        }
+    }
+
     pub fn go(&mut self) -> Option<Vec<Span<Node>>> {
         let mut nodes = vec![];
         loop {
             match self.peek().contents {
-                Token::DocComment(_) => {
-                    self.next(); // one day there will be doc comment support
-                },
+                Token::DocComment(_) => self.doc_comments(),
                 Token::Newline => {
                     self.next();
                 },
                 _ => {
-                    nodes.push(self.statement()?);
-                    self.ensure_next(Token::Newline)?;
+                    let start = self.index;
+                    match self.statement() {
+                        Some(node) => {
+                            nodes.push(node);
+                            if self.ensure_next(Token::Newline).is_none() {
+                                let start = self.index;
+                                nodes.push(self.recover(start));
+                            }
+                        }
+                        None => nodes.push(self.recover(start)),
+                    }
+                    // a stray closing brace is skipped, so that parsing moves on
+                    if self.index == start {
+                        self.next();
+                    }
                 }
             };
             if self.peek().contents == Token::EOF {
@@ -117,7 +163,7 @@ impl<'p> Parser<'p> {
 
     fn statement(&mut self) -> Option<Span<Node>> {
         Some(match self.peek().contents {
-            Token::If => self.if_statement(true)?,
+            Token::If => self.if_statement()?,
             Token::While => self.while_statement()?,
             Token::Loop => self.loop_statement()?,
             Token::Var => self.var_statement()?,
@@ -129,94 +175,212 @@ impl<'p> Parser<'p> {
             Token::Continue => self.continue_statement()?,
             _ => {
                 let saved_index = self.index;
+                let snapshot = self.diagnostics.snapshot();
                 if let Some(stat) = self.assign_statement() {
                     stat
                 } else {
                     self.index = saved_index;
-                    crate::errors::ERRORS.lock().unwrap().pop().unwrap();
+                    self.diagnostics.rollback(snapshot);
                     self.expr(0)?
                 }
             }
         })
     }
 
-    fn if_statement(&mut self, ensure_if: bool) -> Option<Span<Node>> {
-        if ensure_if {
-            self.ensure_next(Token::If)?;
+    /// Parses an `if`, or the `elif` continuing one
+    fn if_statement(&mut self) -> Option<Span<Node>> {
+        let pos = self.peek().pos;
+        if self.try_next(Token::If).is_none() {
+            self.ensure_next(Token::Elif)?;
         }
         let condition = self.expr(0)?;
         let body = self.block()?;
         let else_body;
         if self.peek().contents == Token::Elif {
-            self.ensure_next(Token::Elif)?;
-            else_body = self.if_statement(false)?;
+            else_body = self.if_statement()?;
         } else if self.peek().contents == Token::Else {
             self.ensure_next(Token::Else)?;
             else_body = self.block()?;
         } else {
-            else_body = spanned(Node::Block {
+            // an empty else block, which sits just after the body
+            let end = body.pos + body.len;
+            else_body = self.spanned(Node::Block {
                 nodes: vec![
-                    spanned(Node::Literal {
+                    self.spanned(Node::Literal {
                         typ: Type::Undefined,
                         value: "undefined".to_owned(),
-                    }, 0, 0)
+                    }, end, 0)
                 ],
-            }, 0, 0);
+            }, end, 0);
         }
 
-        Some(spanned(Node::IfStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::IfStatement {
             condition: Box::new(condition),
-            body: Box::new(body.clone()),
+            body: Box::new(body),
             else_body: Box::new(else_body),
-        }, 0, 0))
+        }, pos, len))
     }
 
     fn while_statement(&mut self) -> Option<Span<Node>> {
+        let pos = self.peek().pos;
         self.ensure_next(Token::While)?;
         let condition = self.expr(0)?;
         let body = self.block()?;
 
-        Some(spanned(Node::WhileStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::WhileStatement {
             condition: Box::new(condition),
-            body: Box::new(body.clone()),
-        }, 0, 0))
+            body: Box::new(body),
+        }, pos, len))
     }
 
     fn loop_statement(&mut self) -> Option<Span<Node>> {
+        let keyword = self.peek();
         self.ensure_next(Token::Loop)?;
-        let condition = spanned(Node::Literal {
+        // the condition is always true, so it is put on the loop keyword
+        let condition = self.spanned(Node::Literal {
             typ: Type::Bool,
             value: "true".to_owned(),
-        }, 0, 0);
+        }, keyword.pos, keyword.len);
         let body = self.block()?;
 
-        Some(spanned(Node::WhileStatement {
+        let len = self.len_since(keyword.pos);
+        Some(self.spanned(Node::WhileStatement {
             condition: Box::new(condition),
-            body: Box::new(body.clone()),
-        }, 0, 0))
+            body: Box::new(body),
+        }, keyword.pos, len))
     }
 
     fn block(&mut self) -> Option<Span<Node>> {
+        self.nested(Self::block_contents)
+    }
+
+    fn block_contents(&mut self) -> Option<Span<Node>> {
         let mut nodes = vec![];
+        let open = self.peek();
         self.ensure_next(Token::LBrace)?;
         loop {
             let _ = self.try_next(Token::Newline);
-            nodes.push(self.statement()?);
-            if self.try_next(Token::Newline).is_none() {
-                self.ensure_next(Token::RBrace)?;
-                break;
+            if let Token::DocComment(_) = self.peek().contents {
+                self.doc_comments();
+                if self.try_next(Token::RBrace).is_some() {
+                    break;
+                }
             }
-            if self.peek().contents == Token::RBrace {
-                self.ensure_next(Token::RBrace)?;
+            let start = self.index;
+            let mut recovered = false;
+            match self.statement() {
+                Some(node) => {
+                    nodes.push(node);
+                    let found = self.peek().contents;
+                    // running into the end of the file is reported below
+                    if found != Token::EOF && found != Token::RBrace && self.try_next(Token::Newline).is_none() {
+                        self.expected_rbrace(&open);
+                        let start = self.index;
+                        nodes.push(self.recover(start));
+                        recovered = true;
+                    }
+                }
+                None => {
+                    nodes.push(self.recover(start));
+                    recovered = true;
+                }
+            }
+            if self.try_next(Token::RBrace).is_some() {
                 break;
             }
+            match self.peek().contents {
+                Token::EOF => {
+                    self.expected_rbrace(&open);
+                    return None
+                }
+                // recovery stops at the next proc or const, which can't be inside a block
+                Token::Proc | Token::Const if recovered => {
+                    self.expected_rbrace(&open);
+                    return None
+                }
+                _ => (),
+            }
         }
-        Some(spanned(Node::Block {
+        let len = self.len_since(open.pos);
+        Some(self.spanned(Node::Block {
             nodes,
-        }, 0, 0))
+        }, open.pos, len))
+    }
+
+    /// Reads a run of doc comments, which are kept for a proc or const straight after them
+    fn doc_comments(&mut self) {
+        let pos = self.peek().pos;
+        let mut lines = vec![];
+        while let Token::DocComment(line) = self.peek().contents {
+            self.next();
+            let _ = self.try_next(Token::Newline);
+            lines.push(line.strip_prefix(' ').unwrap_or(&line).to_owned());
+        }
+        if let Token::Proc | Token::Const = self.peek().contents {
+            self.docs = lines.join("\n");
+        } else {
+            let end = self.tokens[..self.index].iter().rev().find(|t| t.contents != Token::Newline);
+            let len = end.map_or(0, |t| t.pos + t.len - pos);
+            self.diagnostics.lint(
+                &UNUSED_DOC_COMMENTS,
+                Error::new(ErrorType::Lint, "This doc comment doesn't document anything", self.file, pos, len)
+                    .help("doc comments go just before a proc or const, use `#` for an ordinary comment"),
+            );
+        }
+    }
+
+    fn expected_rbrace(&mut self, open: &Span<Token>) {
+        let found = self.peek();
+        self.diagnostics.emit(
+            Error::new(
+                SyntaxError,
+                format!("Expected a RBrace token, but found a {:?} instead", found.contents).as_str(),
+                self.file,
+                found.pos,
+                found.len,
+            )
+            .code("E0002")
+            .label("the block starts here", open.pos, open.len),
+        );
+    }
+
+    /// Skips the rest of a statement which failed to parse, starting over from the token at `start`
+    ///
+    /// Parsing picks up again after the next newline, or before a `}` closing the
+    /// block, or before a `proc` or `const` starting a line (where a block was
+    /// probably left open). Blocks inside the statement are skipped as a whole.
+    fn recover(&mut self, start: usize) -> Span<Node> {
+        self.index = start;
+        let pos = self.peek().pos;
+        let mut depth = 0usize;
+        loop {
+            match self.peek().contents {
+                Token::EOF => break,
+                Token::Newline if depth == 0 => {
+                    let len = self.len_since(pos);
+                    self.next();
+                    return self.spanned(Node::Error, pos, len);
+                }
+                Token::RBrace if depth == 0 => break,
+                Token::Proc | Token::Const
+                    if self.index > start && self.tokens[self.index - 1].contents == Token::Newline =>
+                {
+                    break
+                }
+                Token::LBrace => depth += 1,
+                Token::RBrace => depth -= 1,
+                _ => (),
+            }
+            self.next();
+        }
+        let len = self.len_since(pos);
+        self.spanned(Node::Error, pos, len)
     }
 
     fn var_statement(&mut self) -> Option<Span<Node>> {
+        let pos = self.peek().pos;
         self.ensure_next(Token::Var)?;
         let name = self.ensure_ident()?;
         let typ;
@@ -230,20 +394,24 @@ impl<'p> Parser<'p> {
             self.ensure_next(Token::Equals)?;
             value = self.expr(0)?;
         } else {
-            value = spanned(Node::Literal {
+            // the missing value is put just after the declaration
+            let end = pos + self.len_since(pos);
+            value = self.spanned(Node::Literal {
                 typ: Type::Undefined,
                 value: "undefined".to_owned(),
-            }, 0, 0);
+            }, end, 0);
         }
 
-        Some(spanned(Node::VarStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::VarStatement {
             name,
             typ,
             value: Box::new(value),
-        }, 0, 0))
+        }, pos, len))
     }
 
     fn assign_statement(&mut self) -> Option<Span<Node>> {
+        let pos = self.peek().pos;
         let name = self.ensure_ident()?;
         if self.try_next(Token::Equals).is_none() {
             // indexed
@@ -253,22 +421,26 @@ impl<'p> Parser<'p> {
             self.ensure_next(Token::Equals)?;
             let value = self.expr(0)?;
 
-            return Some(spanned(Node::IndexedAssignStatement {
+            let len = self.len_since(pos);
+            return Some(self.spanned(Node::IndexedAssignStatement {
                 name,
                 index: Box::new(index),
                 value: Box::new(value),
-            }, 0, 0));
+            }, pos, len));
         }
 
         let value = self.expr(0)?;
 
-        Some(spanned(Node::AssignStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::AssignStatement {
             name,
             value: Box::new(value),
-        }, 0, 0))
+        }, pos, len))
     }
 
     fn const_statement(&mut self) -> Option<Span<Node>> {
+        let docs = mem::take(&mut self.docs);
+        let pos = self.peek().pos;
         self.ensure_next(Token::Const)?;
         let name = self.ensure_ident()?;
         let typ;
@@ -280,14 +452,18 @@ impl<'p> Parser<'p> {
         self.ensure_next(Token::Equals)?;
         let value = self.expr(0)?;
 
-        Some(spanned(Node::ConstStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::ConstStatement {
             name,
             typ,
             value: Box::new(value),
-        }, 0, 0))
+            docs,
+        }, pos, len))
     }
 
     fn proc_statement(&mut self) -> Option<Span<Node>> {
+        let docs = mem::take(&mut self.docs);
+        let pos = self.peek().pos;
         self.ensure_next(Token::Proc)?;
         let name = self.ensure_ident()?;
         self.ensure_next(Token::LParen)?;
@@ -314,38 +490,43 @@ impl<'p> Parser<'p> {
         if self.peek().contents == Token::LBrace {
             body = self.block()?;
         } else {
-            body = spanned(Node::Block {
+            body = self.spanned(Node::Block {
                 nodes: vec![],
-            }, 0, 0);
+            }, pos + self.len_since(pos), 0);
         }
 
-        Some(spanned(Node::ProcStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::ProcStatement {
             name,
             args,
             arg_types,
             ret_type,
             body: Box::new(body),
-        }, 0, 0))
+            docs,
+        }, pos, len))
     }
 
     fn return_statement(&mut self) -> Option<Span<Node>> {
+        let keyword = self.peek();
         self.ensure_next(Token::Return)?;
         if self.try_next(Token::Newline).is_some() {
-            Some(spanned(Node::ReturnStatement {
-                val: Box::new(spanned(Node::Literal {
+            Some(self.spanned(Node::ReturnStatement {
+                val: Box::new(self.spanned(Node::Literal {
                     typ: Type::Undefined,
                     value: "undefined".to_owned(),
-                }, 0, 0)),
-            }, 0, 0))
+                }, keyword.pos + keyword.len, 0)),
+            }, keyword.pos, keyword.len))
         } else {
             let val = self.expr(0)?;
-            Some(spanned(Node::ReturnStatement {
+            let len = self.len_since(keyword.pos);
+            Some(self.spanned(Node::ReturnStatement {
                 val: Box::new(val),
-            }, 0, 0))
+            }, keyword.pos, len))
         }
     }
 
     fn use_statement(&mut self) -> Option<Span<Node>> {
+        let pos = self.peek().pos;
         self.ensure_next(Token::Use)?;
         let mut path = String::new();
         loop {
@@ -361,27 +542,55 @@ impl<'p> Parser<'p> {
                 break;
             }
         }
-        Some(spanned(Node::UseStatement {
+        let len = self.len_since(pos);
+        Some(self.spanned(Node::UseStatement {
             path,
-        }, 0, 0))
+        }, pos, len))
     }
 
     fn break_statement(&mut self) -> Option<Span<Node>> {
+        let keyword = self.peek();
         self.ensure_next(Token::Break)?;
-        Some(spanned(Node::BreakStatement, 0, 0))
+        Some(self.spanned(Node::BreakStatement, keyword.pos, keyword.len))
     }
 
     fn continue_statement(&mut self) -> Option<Span<Node>> {
+        let keyword = self.peek();
         self.ensure_next(Token::Continue)?;
-        Some(spanned(Node::ContinueStatement, 0, 0))
+        Some(self.spanned(Node::ContinueStatement, keyword.pos, keyword.len))
     }
 
     fn expr(&mut self, min_bp: u8) -> Option<Span<Node>> {
+        self.nested(|parser| parser.expr_bp(min_bp))
+    }
+
+    /// Parses something which can hold itself, as long as that isn't nested so deeply that
+    /// the parser would run out of stack
+    fn nested(&mut self, parse: impl FnOnce(&mut Self) -> Option<Span<Node>>) -> Option<Span<Node>> {
+        if self.depth >= MAX_DEPTH {
+            let token = self.peek();
+            self.diagnostics.syntax_error(
+                "E0028",
+                format!("This is nested more than {} levels deep", MAX_DEPTH).as_str(),
+                self.file,
+                token.pos,
+                token.len,
+            );
+            return None;
+        }
+        self.depth += 1;
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    fn expr_bp(&mut self, min_bp: u8) -> Option<Span<Node>> {
         let mut left = match self.next().clone() {
             Span {
                 contents: Token::Ident(id),
                 pos,
                 len,
+                ..
             } => {
                 if self.peek().contents == Token::LParen {
                     self.next(); // pass the LParen;
@@ -395,56 +604,74 @@ impl<'p> Parser<'p> {
                         }
                     }
                     self.ensure_next(Token::RParen)?;
-                    spanned(Node::Call {
+                    let len = self.len_since(pos);
+                    self.spanned(Node::Call {
                         name: id,
                         args,
                     }, pos, len)
                 } else {
-                    spanned(Node::VariableRef {
+                    self.spanned(Node::VariableRef {
                         name: id,
                     }, pos, len)
                 }
             }
             Span {
-                contents: Token::IntLiteral(int),
+                contents: Token::IntLiteral(int, suffix),
                 pos,
                 len,
-            } => spanned(Node::Literal {
-                typ: Type::IntLiteral,
+                ..
+            } => self.spanned(Node::Literal {
+                typ: suffix.unwrap_or(Type::IntLiteral),
                 value: int,
             }, pos, len),
             Span {
-                contents: Token::FloatLiteral(float),
+                contents: Token::FloatLiteral(float, suffix),
                 pos,
                 len,
-            } => spanned(Node::Literal {
-                typ: Type::FloatLiteral,
+                ..
+            } => self.spanned(Node::Literal {
+                typ: suffix.unwrap_or(Type::FloatLiteral),
                 value: float,
             }, pos, len),
             Span {
                 contents: Token::StrLiteral(s),
                 pos,
                 len,
-            } => spanned(Node::Literal {
+                ..
+            } => self.spanned(Node::Literal {
                 typ: Type::StrLiteral,
                 value: s,
             }, pos, len),
+            Span {
+                contents: Token::CharLiteral(ch),
+                pos,
+                len,
+                ..
+            } => self.spanned(Node::Literal {
+                typ: Type::N8,
+                value: (ch as u32).to_string(),
+            }, pos, len),
             Span {
                 contents: Token::LParen,
+                pos,
                 ..
             } => {
-                let left = self.expr(0)?;
+                let mut left = self.expr(0)?;
                 self.ensure_next(Token::RParen)?;
+                // the parentheses are part of the expression
+                left.pos = pos;
+                left.len = self.len_since(pos);
                 left
             }
             Span {
                 contents: Token::Op(op),
                 pos,
-                len,
-            } => {
-                let ((), right_bp) = prefix_binding_power(&op);
+                ..
+            } if prefix_binding_power(&op).is_some() => {
+                let ((), right_bp) = prefix_binding_power(&op)?;
                 let right = self.expr(right_bp)?;
-                spanned(Node::PrefixOp {
+                let len = self.len_since(pos);
+                self.spanned(Node::PrefixOp {
                     op,
                     right: Box::new(right),
                 }, pos, len)
@@ -453,11 +680,21 @@ impl<'p> Parser<'p> {
                 contents: Token::EOF,
                 pos,
                 len,
+                ..
             } => {
-                Logger::syntax_error("Encountered the end of the file while parsing", pos, len);
+                self.diagnostics.syntax_error("E0006", "Encountered the end of the file while parsing", self.file, pos, len);
+                return None
+            }
+            t => {
+                self.diagnostics.syntax_error(
+                    "E0012",
+                    format!("Expected an expression, but found a {:?} token instead", t.contents).as_str(),
+                    self.file,
+                    t.pos,
+                    t.len,
+                );
                 return None
             }
-            t => panic!("Bad token: {:?}", t),
         };
 
         loop {
@@ -471,7 +708,8 @@ impl<'p> Parser<'p> {
                 | Token::RBrace => break,
                 Token::Op(op) => op,
                 Token::LBracket => "[".to_owned(),
-                t => panic!("Bad token: {:?}", t),
+                // whatever comes next is reported by the statement being parsed
+                _ => break,
             };
 
             if let Some((left_bp, ())) = postfix_binding_power(&op) {
@@ -480,18 +718,19 @@ impl<'p> Parser<'p> {
                 }
                 self.next();
 
+                let pos = left.pos;
                 left = if op == "[" {
                     let right = self.expr(0)?;
                     self.ensure_next(Token::RBracket)?;
-                    spanned(Node::IndexOp {
+                    self.spanned(Node::IndexOp {
                         object: Box::new(left),
                         index: Box::new(right),
-                    }, 0, 0)
+                    }, pos, self.len_since(pos))
                 } else {
-                    spanned(Node::PostfixOp {
+                    self.spanned(Node::PostfixOp {
                         op,
                         left: Box::new(left),
-                    }, 0, 0)
+                    }, pos, self.len_since(pos))
                 };
                 continue;
             }
@@ -503,11 +742,12 @@ impl<'p> Parser<'p> {
                 self.next();
 
                 let right = self.expr(right_bp)?;
-                left = spanned(Node::InfixOp {
+                let pos = left.pos;
+                left = self.spanned(Node::InfixOp {
                     op,
                     left: Box::new(left),
                     right: Box::new(right),
-                }, 0, 0);
+                }, pos, self.len_since(pos));
                 continue;
             }
 
@@ -518,26 +758,28 @@ impl<'p> Parser<'p> {
     }
 }
 
-fn prefix_binding_power(op: &String) -> ((), u8) {
-    match op.as_str() {
+fn prefix_binding_power(op: &str) -> Option<((), u8)> {
+    Some(match op {
         "!" => ((), 8),
-        "+" | "-" => ((), 9),
-        o => unreachable!(o),
-    }
+        "+" | "-" | "-~" => ((), 9),
+        _ => return None,
+    })
 }
 
-fn postfix_binding_power(op: &String) -> Option<(u8, ())> {
-    Some(match op.as_str() {
+fn postfix_binding_power(op: &str) -> Option<(u8, ())> {
+    Some(match op {
         "[" => (11, ()),
         _ => return None,
     })
 }
 
-fn infix_binding_power(op: &String) -> Option<(u8, u8)> {
-    Some(match op.as_str() {
+fn infix_binding_power(op: &str) -> Option<(u8, u8)> {
+    Some(match op {
         ">" | "<" | ">=" | "<=" | "==" | "!=" => (3, 4),
-        "+" | "-" => (5, 6),
-        "*" | "/" | "//" => (7, 8),
-        _ => return None,
+        "+" | "-" | "+~" | "-~" => (5, 6),
+        "*" | "/" | "//" | "*~" => (7, 8),
+        "!" | "." => return None,
+        // an unknown operator, which the lexer has already reported
+        _ => (1, 2),
     })
 }
@@ -0,0 +1,335 @@
+//! Command line argument handling
+
+use std::path::Path;
+
+use elgin::lints::{self, Lint, LintLevel};
+use elgin::llvm::OptLevel;
+
+pub const USAGE: &str = "\
+Usage: elgin <command> [options] <file>
+
+Commands:
+    build           Compile <file> to an executable
+    check           Check <file> for errors without generating any code
+    run             Compile <file> in memory and run its main proc
+    emit <stages>   Write the output of the given compiler stages only
+    doc             Write HTML and Markdown documentation for the procs
+                    and consts in <file> with `#:` comments
+    test --doc      Compile and run the code blocks in the `#:` comments
+                    of <file>, reporting which of them fail
+    explain <code>  Explain an error code, such as E0001, in detail
+
+Options:
+    -o <path>       Write the output to <path> (by default the executable
+                    is <file> without its extension, and the documentation
+                    goes in the directory <file>.doc)
+    --emit=<stages> Also write the output of each of the given stages
+                    to <file>.<stage extension>
+    -O<level>       Optimize the generated code, where <level> is
+                    0 (the default), 1, 2, 3 or s (for size)
+    -g              Generate DWARF debug info
+    -W <lint>       Warn about code caught by <lint>
+    -A <lint>       Allow code caught by <lint>
+    -D <lint>       Deny code caught by <lint>, making it an error
+    -Werror         Make every warning an error
+    -v, --verbose   Trace type inference while compiling
+    --error-format=<format>
+                    Print errors as `human` readable text (the default)
+                    or as one `json` object per line
+    -h, --help      Print this message
+
+Stages (comma separated):
+    tokens, ast, eir, llvm-ir, bc, asm, obj";
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Stage {
+    Tokens,
+    Ast,
+    Eir,
+    LlvmIr,
+    Bitcode,
+    Asm,
+    Obj,
+}
+
+impl Stage {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Stage::Tokens => "tokens",
+            Stage::Ast => "ast",
+            Stage::Eir => "eir",
+            Stage::LlvmIr => "ll",
+            Stage::Bitcode => "bc",
+            Stage::Asm => "s",
+            Stage::Obj => "o",
+        }
+    }
+
+    /// Whether the artifact is readable text which can go to stdout
+    pub fn is_text(self) -> bool {
+        self <= Stage::LlvmIr || self == Stage::Asm
+    }
+}
+
+/// The usage message followed by the list of lints
+pub fn help() -> String {
+    let mut help = format!("{}\n\nLints:\n", USAGE);
+    for lint in lints::LINTS {
+        help.push_str(&format!("    {:<20}{}\n", lint.name, lint.desc));
+    }
+    help
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Build,
+    Check,
+    Run,
+    Emit,
+    Doc,
+    Test,
+    Explain,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub input: String,
+    pub output: Option<String>,
+    pub emit: Vec<Stage>,
+    pub opt_level: OptLevel,
+    pub debug_info: bool,
+    pub verbose: bool,
+    pub error_format: ErrorFormat,
+    pub lints: Vec<(&'static Lint, LintLevel)>,
+    pub warnings_as_errors: bool,
+}
+
+impl Options {
+    /// The path an artifact ending in `extension` is written to when no `-o` is given
+    pub fn default_output(&self, extension: &str) -> String {
+        format!("{}.{}", self.input, extension)
+    }
+
+    pub fn default_executable(&self) -> String {
+        let path = Path::new(&self.input);
+        if path.extension().is_some() {
+            path.with_extension("").to_string_lossy().into_owned()
+        } else {
+            self.default_output("out")
+        }
+    }
+
+    pub fn emits(&self, stage: Stage) -> bool {
+        self.emit.contains(&stage)
+    }
+
+    /// Where the artifact for `stage` goes, with `None` meaning stdout
+    pub fn emit_path(&self, stage: Stage) -> Option<String> {
+        if self.command == Command::Emit && self.emit.len() == 1 {
+            if let Some(output) = &self.output {
+                return Some(output.clone());
+            } else if stage.is_text() {
+                return None;
+            }
+        }
+        Some(self.default_output(stage.extension()))
+    }
+
+    /// Whether every requested stage has been emitted once `stage` is done
+    pub fn done_after(&self, stage: Stage) -> bool {
+        self.command == Command::Emit && self.emit.iter().all(|s| *s <= stage)
+    }
+}
+
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let command = match args.next().as_deref() {
+        Some("build") => Command::Build,
+        Some("check") => Command::Check,
+        Some("run") => Command::Run,
+        Some("emit") => Command::Emit,
+        Some("doc") => Command::Doc,
+        Some("test") => Command::Test,
+        Some("explain") => {
+            return match args.next() {
+                Some(code) => Ok(Options {
+                    command: Command::Explain,
+                    input: code,
+                    output: None,
+                    emit: vec![],
+                    opt_level: OptLevel::O0,
+                    debug_info: false,
+                    verbose: false,
+                    error_format: ErrorFormat::Human,
+                    lints: vec![],
+                    warnings_as_errors: false,
+                }),
+                None => Err("Expected an error code to explain".to_owned()),
+            }
+        }
+        Some("-h") | Some("--help") | Some("help") => Command::Help,
+        Some(c) => return Err(format!("Unknown command `{}`", c)),
+        None => return Err("Expected a command".to_owned()),
+    };
+
+    let mut input = None;
+    let mut output = None;
+    let mut emit = vec![];
+    let mut opt_level = OptLevel::O0;
+    let mut debug_info = false;
+    let mut verbose = false;
+    let mut error_format = ErrorFormat::Human;
+    let mut lints = vec![];
+    let mut warnings_as_errors = false;
+    // `elgin test` only runs doc-tests so far, but still asks for them by name
+    let mut doc = false;
+    if command == Command::Emit {
+        match args.next() {
+            Some(stages) => emit = parse_stages(&stages)?,
+            None => return Err("Expected the stages to emit".to_owned()),
+        }
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => match args.next() {
+                Some(path) => output = Some(path),
+                None => return Err("Expected a path after `-o`".to_owned()),
+            },
+            "--emit" => match args.next() {
+                Some(stages) => emit.append(&mut parse_stages(&stages)?),
+                None => return Err("Expected the stages to emit after `--emit`".to_owned()),
+            },
+            a if a.starts_with("--emit=") => {
+                emit.append(&mut parse_stages(&a["--emit=".len()..])?);
+            }
+            "-O0" => opt_level = OptLevel::O0,
+            "-O1" => opt_level = OptLevel::O1,
+            "-O2" => opt_level = OptLevel::O2,
+            "-O3" => opt_level = OptLevel::O3,
+            "-Os" => opt_level = OptLevel::Os,
+            "-g" => debug_info = true,
+            "-Werror" => warnings_as_errors = true,
+            "-W" | "-A" | "-D" => match args.next() {
+                Some(name) => lints.push(parse_lint(&arg, &name)?),
+                None => return Err(format!("Expected a lint after `{}`", arg)),
+            },
+            a if a.starts_with("-W") || a.starts_with("-A") || a.starts_with("-D") => {
+                lints.push(parse_lint(&a[..2], &a[2..])?);
+            }
+            "-v" | "--verbose" => verbose = true,
+            "--doc" => doc = true,
+            "--error-format" => match args.next() {
+                Some(format) => error_format = parse_error_format(&format)?,
+                None => return Err("Expected a format after `--error-format`".to_owned()),
+            },
+            a if a.starts_with("--error-format=") => {
+                error_format = parse_error_format(&a["--error-format=".len()..])?;
+            }
+            "-h" | "--help" => {
+                return Ok(Options {
+                    command: Command::Help,
+                    input: String::new(),
+                    output: None,
+                    emit,
+                    opt_level,
+                    debug_info,
+                    verbose,
+                    error_format,
+                    lints,
+                    warnings_as_errors,
+                })
+            }
+            a if a.starts_with('-') => return Err(format!("Unknown option `{}`", a)),
+            _ => {
+                if input.is_some() {
+                    return Err(format!("Unexpected argument `{}`", arg));
+                }
+                input = Some(arg);
+            }
+        }
+    }
+
+    let input = match (input, &command) {
+        (Some(input), _) => input,
+        (None, Command::Help) => String::new(),
+        (None, _) => return Err("Expected a file to compile".to_owned()),
+    };
+
+    if command == Command::Test && !doc {
+        return Err("`elgin test` only runs doc-tests so far, so it needs `--doc`".to_owned());
+    }
+    if command != Command::Test && doc {
+        return Err("`--doc` only goes with `elgin test`".to_owned());
+    }
+
+    if command == Command::Emit && emit.len() > 1 && output.is_some() {
+        return Err("`-o` can't be used when emitting more than one stage".to_owned());
+    }
+    let name = match command {
+        Command::Check => Some("check"),
+        Command::Doc => Some("doc"),
+        Command::Test => Some("test"),
+        _ => None,
+    };
+    if let (Some(name), true) = (name, emit.iter().any(|&stage| stage > Stage::Eir)) {
+        return Err(format!(
+            "`elgin {}` doesn't generate any code, so it can only emit tokens, ast or eir",
+            name
+        ));
+    }
+
+    Ok(Options {
+        command,
+        input,
+        output,
+        emit,
+        opt_level,
+        debug_info,
+        verbose,
+        error_format,
+        lints,
+        warnings_as_errors,
+    })
+}
+
+fn parse_lint(flag: &str, name: &str) -> Result<(&'static Lint, LintLevel), String> {
+    let lint = lints::find(name).ok_or_else(|| format!("Unknown lint `{}`", name))?;
+    let level = match flag {
+        "-W" => LintLevel::Warn,
+        "-A" => LintLevel::Allow,
+        _ => LintLevel::Deny,
+    };
+    Ok((lint, level))
+}
+
+fn parse_error_format(s: &str) -> Result<ErrorFormat, String> {
+    match s {
+        "human" => Ok(ErrorFormat::Human),
+        "json" => Ok(ErrorFormat::Json),
+        _ => Err(format!("Unknown error format `{}`", s)),
+    }
+}
+
+fn parse_stages(s: &str) -> Result<Vec<Stage>, String> {
+    s.split(',').map(parse_stage).collect()
+}
+
+fn parse_stage(s: &str) -> Result<Stage, String> {
+    Ok(match s {
+        "tokens" => Stage::Tokens,
+        "ast" => Stage::Ast,
+        "eir" => Stage::Eir,
+        "llvm-ir" => Stage::LlvmIr,
+        "bc" => Stage::Bitcode,
+        "asm" => Stage::Asm,
+        "obj" => Stage::Obj,
+        _ => return Err(format!("Unknown stage `{}`", s)),
+    })
+}
@@ -0,0 +1,708 @@
+//! Error codes and their long explanations, shown by `elgin explain`
+
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub explanation: &'static str,
+}
+
+pub static CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "E0001",
+        explanation: "\
+A string literal was never closed.
+
+Every string literal starts and ends with a `\"` on the same line. When the
+closing quote is missing, the rest of the file is read as part of the string
+until it runs out. A raw string such as `r#\"...\"#` ends at a `\"` followed by
+as many `#`s as it started with.
+
+Erroneous code example:
+
+    proc main() {
+        puts(\"Hello world!)
+    }
+
+Add the missing quote:
+
+    proc main() {
+        puts(\"Hello world!\")
+    }
+",
+    },
+    ErrorCode {
+        code: "E0002",
+        explanation: "\
+The parser needed one particular token next, but found something else.
+
+The message names the token which was expected. The most common case is
+`Expected a Newline token`: every statement, including a whole `if`, `while`
+or `proc` once its closing `}` is reached, has to end at a line break. Code
+after a `}` on the same line is an error, unless it is the `elif` or `else`
+belonging to an `if`.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        return answer()
+    } proc answer(): i32 {
+        return 42
+    }
+
+Start the next statement on a new line:
+
+    proc main(): i32 {
+        return answer()
+    }
+    proc answer(): i32 {
+        return 42
+    }
+",
+    },
+    ErrorCode {
+        code: "E0003",
+        explanation: "\
+A name was expected, but something else was found.
+
+The names of variables, constants, procs and proc arguments are identifiers:
+a letter or `_`, followed by any letters, digits and `_`s. Keywords like
+`var` and `proc` can't be used as names.
+
+Erroneous code example:
+
+    proc main() {
+        var 1st = 1
+    }
+
+Pick a name which starts with a letter:
+
+    proc main() {
+        var first = 1
+    }
+",
+    },
+    ErrorCode {
+        code: "E0004",
+        explanation: "\
+A type was expected, but something else was found.
+
+Types follow a `:` after a variable, argument or proc header. They are one of
+the built in types `i8` to `i128`, `n8` to `n128`, `f32`, `f64`, `f128` and
+`bool`, a pointer `*T`, or an array `[N]T`.
+
+Erroneous code example:
+
+    proc main() {
+        var x: int = 1
+    }
+
+Use one of the built in types:
+
+    proc main() {
+        var x: i32 = 1
+    }
+",
+    },
+    ErrorCode {
+        code: "E0005",
+        explanation: "\
+The length of an array type wasn't an integer literal.
+
+Arrays have a length fixed when the program is compiled, so it has to be
+written out as a number between the brackets.
+
+Erroneous code example:
+
+    proc main() {
+        var a: [n]i32
+    }
+
+Write the length as a number:
+
+    proc main() {
+        var a: [10]i32
+    }
+",
+    },
+    ErrorCode {
+        code: "E0006",
+        explanation: "\
+The file ended in the middle of an expression.
+
+This usually means that the last line of the file is cut off, for example
+after an operator or inside parentheses.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        return 1 +
+
+Finish the expression and close any open blocks:
+
+    proc main(): i32 {
+        return 1 + 2
+    }
+",
+    },
+    ErrorCode {
+        code: "E0007",
+        explanation: "\
+A statement was found outside of any proc.
+
+Only `proc` and `const` statements can appear at the top level of a module.
+Everything else has to be inside the body of a proc.
+
+Erroneous code example:
+
+    var start = 10
+
+    proc main(): i32 {
+        return start
+    }
+
+Move the statement into a proc, or make it a constant:
+
+    const start = 10
+
+    proc main(): i32 {
+        return start
+    }
+",
+    },
+    ErrorCode {
+        code: "E0008",
+        explanation: "\
+A `const` statement was found inside a proc.
+
+Constants can currently only be declared at the top level of a module.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        const answer = 42
+        return answer
+    }
+
+Move the constant out of the proc:
+
+    const answer = 42
+
+    proc main(): i32 {
+        return answer
+    }
+",
+    },
+    ErrorCode {
+        code: "E0009",
+        explanation: "\
+A variable was used which doesn't exist in the current scope.
+
+Variables have to be declared with `var` before they are used. Check the
+spelling of the name, too.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        var count = 1
+        return cuont
+    }
+
+Use the name the variable was declared with:
+
+    proc main(): i32 {
+        var count = 1
+        return count
+    }
+",
+    },
+    ErrorCode {
+        code: "E0010",
+        explanation: "\
+A proc was called which doesn't exist.
+
+Procs can be called from anywhere in the module they are declared in, before
+or after their declaration, but the name has to match exactly.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        return fibonaci(10)
+    }
+
+    proc fibonacci(n: i32): i32 {
+        return n
+    }
+
+Use the name the proc was declared with:
+
+    proc main(): i32 {
+        return fibonacci(10)
+    }
+
+    proc fibonacci(n: i32): i32 {
+        return n
+    }
+",
+    },
+    ErrorCode {
+        code: "E0011",
+        explanation: "\
+A character was found which can't start any token.
+
+Outside of strings and comments, Elgin source code is made of names, numbers,
+ASCII punctuation and whitespace. Names can use letters from any script, but
+other characters, such as currency symbols or control characters, are errors.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        var price = 5€
+        return price
+    }
+
+Leave the symbol out, or put it in a string:
+
+    proc main(): i32 {
+        var price = 5
+        return price
+    }
+",
+    },
+    ErrorCode {
+        code: "E0012",
+        explanation: "\
+An expression was expected, but something else was found.
+
+Expressions are literals, variables, proc calls, operators applied to other
+expressions and expressions in parentheses. This error is also given when a
+block is left empty, because every block needs at least one statement.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        return * 2
+    }
+
+Give the operator both of its operands:
+
+    proc main(): i32 {
+        return 1 * 2
+    }
+",
+    },
+    ErrorCode {
+        code: "E0013",
+        explanation: "\
+A `proc` or `use` statement was found inside a proc.
+
+Procs can only be declared at the top level of a module, and `use` statements
+aren't supported yet.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        proc answer(): i32 {
+            return 42
+        }
+        return answer()
+    }
+
+Move the inner proc out to the top level:
+
+    proc main(): i32 {
+        return answer()
+    }
+
+    proc answer(): i32 {
+        return 42
+    }
+",
+    },
+    ErrorCode {
+        code: "E0014",
+        explanation: "\
+An operator was used which is parsed, but can't be compiled yet.
+
+At the moment this is the logical not operator `!` and the unary `+`.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        var done = 1 == 2
+        if !done {
+            return 0
+        }
+        return 1
+    }
+
+Swap the branches around instead:
+
+    proc main(): i32 {
+        var done = 1 == 2
+        if done {
+            return 1
+        }
+        return 0
+    }
+",
+    },
+    ErrorCode {
+        code: "E0015",
+        explanation: "\
+A proc was called with the wrong number of arguments.
+
+Every call has to pass exactly one argument for each argument in the proc's
+declaration.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        return add(1)
+    }
+
+    proc add(a: i32, b: i32): i32 {
+        return a + b
+    }
+
+Pass every argument:
+
+    proc main(): i32 {
+        return add(1, 2)
+    }
+
+    proc add(a: i32, b: i32): i32 {
+        return a + b
+    }
+",
+    },
+    ErrorCode {
+        code: "E0016",
+        explanation: "\
+A value which isn't an array was indexed.
+
+Only arrays can be indexed with `[]`, and the array's type has to be known
+where it is indexed, so it has to come from a variable or argument declared
+with an array type.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        var a: i32 = 1
+        return a[0]
+    }
+
+Declare the variable as an array:
+
+    proc main(): i32 {
+        var a: [1]i32
+        a[0] = 1
+        return a[0]
+    }
+",
+    },
+    ErrorCode {
+        code: "E0017",
+        explanation: "\
+A string or character literal holds an escape sequence which doesn't exist.
+
+The escapes are `\\n`, `\\t`, `\\r`, `\\0`, `\\\\`, `\\\"` and `\\'`, a byte
+`\\x00` to `\\x7F` written as exactly two hex digits, and a Unicode character
+`\\u{0}` to `\\u{10FFFF}`.
+
+Erroneous code example:
+
+    proc main() {
+        puts(\"C:\\data\\docs\")
+    }
+
+Escape the backslashes, or use a raw string, which has no escapes:
+
+    proc main() {
+        puts(r\"C:\\data\\docs\")
+    }
+",
+    },
+    ErrorCode {
+        code: "E0018",
+        explanation: "\
+A character literal doesn't hold exactly one ASCII character.
+
+Character literals such as `'a'` or `'\\n'` are n8s, so the character has to
+fit in a single byte. A longer piece of text has to be a string.
+
+Erroneous code example:
+
+    proc main() {
+        var separator = ', '
+    }
+
+Use a single character:
+
+    proc main() {
+        var separator = ','
+    }
+",
+    },
+    ErrorCode {
+        code: "E0019",
+        explanation: "\
+A number literal is malformed.
+
+Integers can be written in decimal, or in hex, octal or binary after a `0x`,
+`0o` or `0b`. Floats are always decimal, and can have an exponent such as
+`1.5e-9`. Both can use `_` to separate digits, and end with the type they
+should have, such as `255n8` or `1f32`. The value has to fit in that type.
+
+Erroneous code example:
+
+    proc main(): n8 {
+        return 256n8
+    }
+
+Use a type which is big enough:
+
+    proc main(): n16 {
+        return 256n16
+    }
+",
+    },
+    ErrorCode {
+        code: "E0020",
+        explanation: "\
+A run of punctuation was found which isn't an operator.
+
+The operators are `+`, `-`, `*`, `/` and `//` for integer division, the
+wrapping `+~`, `-~` and `*~`, the comparisons `==`, `!=`, `<`, `>`, `<=` and
+`>=`, and `!`. Operators which are next to each other are split up by taking
+the longest one first, so `a*-b` is `a * -b`.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        var a = 7
+        return a % 2
+    }
+
+Work out the remainder with the operators which exist:
+
+    proc main(): i32 {
+        var a = 7
+        return a - a // 2 * 2
+    }
+",
+    },
+    ErrorCode {
+        code: "E0021",
+        explanation: "\
+A block comment was never closed.
+
+Block comments start with `#[` and end with `]#`, and can hold other block
+comments, so every `#[` inside one needs its own `]#` too. When one is
+missing, the rest of the file is read as part of the comment.
+
+Erroneous code example:
+
+    #[ answer() returns the answer,
+       #[ which is 42 ]#
+    proc answer(): i32 {
+        return 42
+    }
+
+Close every block comment:
+
+    #[ answer() returns the answer,
+       #[ which is 42 ]#
+    ]#
+    proc answer(): i32 {
+        return 42
+    }
+",
+    },
+    ErrorCode {
+        code: "E0022",
+        explanation: "\
+A `break` or `continue` was found outside of a loop.
+
+`break` leaves the innermost `while` or `loop` around it, and `continue`
+goes back to its start, so both only make sense inside the body of one.
+
+Erroneous code example:
+
+    proc main() {
+        break
+    }
+
+Only use them inside a loop:
+
+    proc main() {
+        loop {
+            break
+        }
+    }
+",
+    },
+    ErrorCode {
+        code: "E0023",
+        explanation: "\
+The type of a variable or expression couldn't be inferred.
+
+Types are inferred from the values given to variables and from how they're
+used, such as a variable being returned from a proc which returns `i32`, and
+untyped literals such as `1` and `1.5` are `i32` and `f64` otherwise. A
+variable declared without a type or a value, and never given one, is left
+without a type.
+
+Erroneous code example:
+
+    proc main() {
+        var x
+    }
+
+Give it a type, or a value:
+
+    proc main() {
+        var x: i32
+        var y = 1
+    }
+",
+    },
+    ErrorCode {
+        code: "E0024",
+        explanation: "\
+A value was used where there isn't one, or is missing where one is needed.
+
+A proc without a return type doesn't give back a value, so its calls can't be
+used as values and its `return`s can't have one. A proc with a return type has
+to return a value of that type.
+
+Erroneous code example:
+
+    proc greet() {
+        puts(\"hello\")
+    }
+
+    proc main(): i32 {
+        var x: i32 = greet()
+        return x
+    }
+
+Give the proc a return type, or call it on its own:
+
+    proc greet() {
+        puts(\"hello\")
+    }
+
+    proc main(): i32 {
+        greet()
+        return 0
+    }
+",
+    },
+    ErrorCode {
+        code: "E0025",
+        explanation: "\
+Two values which have to have the same type have different types.
+
+Elgin never converts between types on its own. The operands of an operator,
+the value stored in a variable, the arguments of a call and the value a proc
+returns all have to match the type they're used as, and a condition has to be
+a `bool`. Arithmetic only works on numbers, and `/` only divides floats, with
+`//` dividing integers.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        var n: i32 = 1
+        if n {
+            return 1
+        }
+        return 0
+    }
+
+Compare the value to get a `bool`:
+
+    proc main(): i32 {
+        var n: i32 = 1
+        if n != 0 {
+            return 1
+        }
+        return 0
+    }
+",
+    },
+    ErrorCode {
+        code: "E0026",
+        explanation: "\
+A proc with a return type can get to its closing brace without returning.
+
+Every way through a proc with a return type has to end in a `return`, so an
+`if` without an `else`, or a loop, can't be the last thing in it.
+
+Erroneous code example:
+
+    proc sign(n: i32): i32 {
+        if n < 0 {
+            return -1
+        }
+    }
+
+Return on every path:
+
+    proc sign(n: i32): i32 {
+        if n < 0 {
+            return -1
+        }
+        return 1
+    }
+",
+    },
+    ErrorCode {
+        code: "E0027",
+        explanation: "\
+A const's value uses the const itself, directly or through other consts.
+
+A const is replaced by its value wherever it's used, so a const which refers
+back to itself would never finish being replaced.
+
+Erroneous code example:
+
+    const a: i32 = b + 1
+    const b: i32 = a
+
+    proc main(): i32 {
+        return a
+    }
+
+Give one of the consts a value which doesn't depend on the other:
+
+    const a: i32 = b + 1
+    const b: i32 = 41
+
+    proc main(): i32 {
+        return a
+    }
+",
+    },
+    ErrorCode {
+        code: "E0028",
+        explanation: "\
+Expressions or blocks are nested too deeply.
+
+The parser handles each level of nesting, such as a pair of parentheses or an
+`if` inside another `if`, by going one level deeper itself, so it stops at 128
+levels rather than running out of stack.
+
+Erroneous code example:
+
+    proc main(): i32 {
+        return ((((((((((...(1)...))))))))))
+    }
+
+Split the expression up with variables, or the code with more procs.
+",
+    },
+];
+
+/// Looks up an error code, ignoring the case of the `E`
+pub fn find(code: &str) -> Option<&'static ErrorCode> {
+    CODES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
@@ -0,0 +1,286 @@
+//! A lossless concrete syntax tree, for tools which rewrite source
+//!
+//! The lexer drops whitespace, comments and repeated newlines. Here every
+//! token keeps the trivia before it, so printing a tree gives back exactly
+//! the source it came from. The nodes follow the AST, and the AST can be
+//! parsed again from the tokens of a tree.
+
+use std::fmt;
+
+use crate::astgen::Node;
+use crate::errors::{Diagnostics, Span};
+use crate::lexer::{Lexer, Token};
+use crate::parser::Parser;
+use crate::source_map::FileId;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    /// Spaces, tabs and the newlines which aren't tokens
+    Whitespace,
+    /// A `#` comment, up to the end of its line
+    Comment,
+    /// A `#[ ]#` comment, including any nested in it
+    BlockComment,
+    /// Text the lexer reported and skipped, such as a stray character or an unterminated string
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub pos: usize,
+}
+
+/// A token along with its text and the trivia just before it
+#[derive(Debug, Clone)]
+pub struct CstToken {
+    pub token: Span<Token>,
+    pub text: String,
+    pub leading: Vec<Trivia>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    File,
+    Literal,
+    Call,
+    InfixOp,
+    PrefixOp,
+    PostfixOp,
+    IndexOp,
+    VariableRef,
+    IfStatement,
+    WhileStatement,
+    Block,
+    VarStatement,
+    ConstStatement,
+    AssignStatement,
+    IndexedAssignStatement,
+    ProcStatement,
+    ReturnStatement,
+    UseStatement,
+    BreakStatement,
+    ContinueStatement,
+    Error,
+}
+
+impl SyntaxKind {
+    fn of(node: &Node) -> Self {
+        match node {
+            Node::Literal { .. } => SyntaxKind::Literal,
+            Node::Call { .. } => SyntaxKind::Call,
+            Node::InfixOp { .. } => SyntaxKind::InfixOp,
+            Node::PrefixOp { .. } => SyntaxKind::PrefixOp,
+            Node::PostfixOp { .. } => SyntaxKind::PostfixOp,
+            Node::IndexOp { .. } => SyntaxKind::IndexOp,
+            Node::VariableRef { .. } => SyntaxKind::VariableRef,
+            Node::IfStatement { .. } => SyntaxKind::IfStatement,
+            Node::WhileStatement { .. } => SyntaxKind::WhileStatement,
+            Node::Block { .. } => SyntaxKind::Block,
+            Node::VarStatement { .. } => SyntaxKind::VarStatement,
+            Node::ConstStatement { .. } => SyntaxKind::ConstStatement,
+            Node::AssignStatement { .. } => SyntaxKind::AssignStatement,
+            Node::IndexedAssignStatement { .. } => SyntaxKind::IndexedAssignStatement,
+            Node::ProcStatement { .. } => SyntaxKind::ProcStatement,
+            Node::ReturnStatement { .. } => SyntaxKind::ReturnStatement,
+            Node::UseStatement { .. } => SyntaxKind::UseStatement,
+            Node::BreakStatement => SyntaxKind::BreakStatement,
+            Node::ContinueStatement => SyntaxKind::ContinueStatement,
+            Node::Error => SyntaxKind::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Element {
+    Node(SyntaxNode),
+    Token(CstToken),
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub pos: usize,
+    pub len: usize,
+    pub children: Vec<Element>,
+}
+
+/// Lexes `source` without losing anything, ending with an EOF token which holds the trailing trivia
+///
+/// A string which runs off the end of the file is reported and kept as skipped trivia.
+pub fn lex(source: &str, file: FileId, diagnostics: &Diagnostics) -> Vec<CstToken> {
+    let (tokens, unterminated) = Lexer::new(source, file, diagnostics).go_partial();
+    let mut lossless = vec![];
+    let mut end = 0;
+    for token in tokens {
+        lossless.push(CstToken {
+            text: source[token.pos..token.pos + token.len].to_owned(),
+            leading: trivia(source, end, token.pos),
+            token,
+        });
+        end = lossless.last().map_or(0, |t| t.token.pos + t.token.len);
+    }
+    let mut leading = trivia(source, end, unterminated.unwrap_or(source.len()));
+    if let Some(pos) = unterminated {
+        leading.push(Trivia {
+            kind: TriviaKind::Skipped,
+            text: source[pos..].to_owned(),
+            pos,
+        });
+    }
+    lossless.push(CstToken {
+        token: Span {
+            contents: Token::EOF,
+            file,
+            pos: source.len(),
+            len: 0,
+        },
+        text: String::new(),
+        leading,
+    });
+    lossless
+}
+
+/// Parses `source` into a tree holding all of it
+///
+/// Code the parser can't make sense of still ends up in the tree, as tokens
+/// outside of any node or in `Error` nodes.
+pub fn parse(source: &str, file: FileId, diagnostics: &Diagnostics) -> SyntaxNode {
+    let tokens = lex(source, file, diagnostics);
+    let significant = significant_tokens(&tokens);
+    let ast = Parser::new(&significant, file, diagnostics).go().unwrap_or_default();
+
+    let mut tokens = tokens.into_iter().peekable();
+    let mut children = vec![];
+    for node in &ast {
+        children.append(&mut build(node, &mut tokens));
+    }
+    children.extend(tokens.map(Element::Token));
+    SyntaxNode {
+        kind: SyntaxKind::File,
+        pos: 0,
+        len: source.len(),
+        children,
+    }
+}
+
+type Tokens = std::iter::Peekable<std::vec::IntoIter<CstToken>>;
+
+/// Takes the tokens up to the end of `node` and makes a tree of them, after
+/// the tokens before it
+///
+/// Every token is taken exactly once whatever the spans are, which keeps
+/// the tree lossless.
+fn build(node: &Span<Node>, tokens: &mut Tokens) -> Vec<Element> {
+    let mut before = take_before(tokens, node.pos);
+    let mut children = vec![];
+    for child in node.contents.children() {
+        children.append(&mut build(child, tokens));
+    }
+    children.append(&mut take_before(tokens, node.pos + node.len));
+    before.push(Element::Node(SyntaxNode {
+        kind: SyntaxKind::of(&node.contents),
+        pos: node.pos,
+        len: node.len,
+        children,
+    }));
+    before
+}
+
+fn take_before(tokens: &mut Tokens, pos: usize) -> Vec<Element> {
+    let mut taken = vec![];
+    while let Some(token) = tokens.next_if(|t| t.token.pos < pos && t.token.contents != Token::EOF) {
+        taken.push(Element::Token(token));
+    }
+    taken
+}
+
+/// The tokens the parser works with, which are everything but the EOF
+fn significant_tokens<'t>(tokens: impl IntoIterator<Item = &'t CstToken>) -> Vec<Span<Token>> {
+    tokens
+        .into_iter()
+        .filter(|t| t.token.contents != Token::EOF)
+        .map(|t| t.token.clone())
+        .collect()
+}
+
+/// Splits the text between two tokens into trivia
+fn trivia(source: &str, start: usize, end: usize) -> Vec<Trivia> {
+    let mut trivia = vec![];
+    let mut pos = start;
+    while pos < end {
+        let rest = &source[pos..end];
+        let (kind, len) = if rest.starts_with(|c: char| c.is_ascii_whitespace()) {
+            let len = rest.find(|c: char| !c.is_ascii_whitespace()).unwrap_or(rest.len());
+            (TriviaKind::Whitespace, len)
+        } else if rest.starts_with("#[") {
+            (TriviaKind::BlockComment, block_comment_len(rest))
+        } else if rest.starts_with('#') {
+            (TriviaKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else {
+            let len = rest.find(|c: char| c.is_ascii_whitespace() || c == '#').unwrap_or(rest.len());
+            (TriviaKind::Skipped, len)
+        };
+        trivia.push(Trivia {
+            kind,
+            text: rest[..len].to_owned(),
+            pos,
+        });
+        pos += len;
+    }
+    trivia
+}
+
+/// The length of the block comment `text` starts with, or all of it if it's unterminated
+fn block_comment_len(text: &str) -> usize {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if text[i..].starts_with("#[") {
+            depth += 1;
+            i += 2;
+        } else if text[i..].starts_with("]#") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += text[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    text.len()
+}
+
+impl SyntaxNode {
+    /// Every token in the tree, in source order
+    pub fn tokens(&self) -> Vec<&CstToken> {
+        let mut tokens = vec![];
+        for child in &self.children {
+            match child {
+                Element::Node(node) => tokens.append(&mut node.tokens()),
+                Element::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// Parses the AST again from the tree's tokens
+    pub fn to_ast(&self, file: FileId, diagnostics: &Diagnostics) -> Option<Vec<Span<Node>>> {
+        Parser::new(&significant_tokens(self.tokens()), file, diagnostics).go()
+    }
+}
+
+/// Prints the exact source the tree was made from
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.tokens() {
+            for trivia in &token.leading {
+                f.write_str(&trivia.text)?;
+            }
+            f.write_str(&token.text)?;
+        }
+        Ok(())
+    }
+}
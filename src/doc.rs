@@ -0,0 +1,394 @@
+//! Documentation generated from the `#:` comments on procs and consts
+//!
+//! Doc comments are Markdown. Inline code naming another documented item,
+//! such as `factorial` or `factorial()`, becomes a link to it, and code
+//! blocks in them are doc-tests (see `tests`).
+
+use std::fmt::Write;
+
+use crate::astgen::Node;
+use crate::errors::Span;
+use crate::source_map::SourceFile;
+use crate::types::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    Proc,
+    Const,
+}
+
+/// A documented proc or const
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub name: String,
+    /// Where the declaration starts, after its docs
+    pub pos: usize,
+    /// The declaration as it would be written, without a body or value
+    pub signature: String,
+    pub docs: String,
+    /// The other documented procs this one calls, in the order they first appear
+    pub calls: Vec<String>,
+}
+
+/// Collects the documented procs and consts at the top level of `ast`, in source order
+pub fn collect(ast: &[Span<Node>]) -> Vec<Item> {
+    let mut items = vec![];
+    for node in ast {
+        match &node.contents {
+            Node::ProcStatement { name, args, arg_types, ret_type, body, docs } if !docs.is_empty() => {
+                let args: Vec<_> = args.iter().zip(arg_types).map(|(a, t)| format!("{}: {:?}", a, t)).collect();
+                let mut signature = format!("proc {}({})", name, args.join(", "));
+                if *ret_type != Type::Undefined {
+                    let _ = write!(signature, ": {:?}", ret_type);
+                }
+                let mut calls = vec![];
+                called_procs(body, &mut calls);
+                items.push(Item {
+                    kind: ItemKind::Proc,
+                    name: name.clone(),
+                    pos: node.pos,
+                    signature,
+                    docs: docs.clone(),
+                    calls,
+                });
+            }
+            Node::ConstStatement { name, typ, docs, .. } if !docs.is_empty() => {
+                let signature = match typ {
+                    // the type is only known once it has been inferred
+                    Type::Variable(_) => format!("const {}", name),
+                    typ => format!("const {}: {:?}", name, typ),
+                };
+                items.push(Item {
+                    kind: ItemKind::Const,
+                    name: name.clone(),
+                    pos: node.pos,
+                    signature,
+                    docs: docs.clone(),
+                    calls: vec![],
+                });
+            }
+            _ => (),
+        }
+    }
+
+    let procs: Vec<_> = items.iter().filter(|i| i.kind == ItemKind::Proc).map(|i| i.name.clone()).collect();
+    for item in &mut items {
+        let name = item.name.clone();
+        item.calls.retain(|call| *call != name && procs.contains(call));
+    }
+    items
+}
+
+/// Renders the items as a single Markdown file
+pub fn markdown(title: &str, items: &[Item]) -> String {
+    let mut out = format!("# {}\n", title);
+    if items.is_empty() {
+        out.push_str("\nNothing here is documented yet.\n");
+    }
+    for item in items {
+        let _ = writeln!(out, "\n<a id=\"{}\"></a>\n## {}\n", item.name, item.name);
+        let _ = writeln!(out, "```\n{}\n```\n", item.signature);
+        let mut in_code = false;
+        for line in item.docs.lines() {
+            if is_fence(line) {
+                in_code = !in_code;
+                out.push_str(line);
+            } else if in_code {
+                out.push_str(line);
+            } else {
+                for (code, text) in code_spans(line) {
+                    match code {
+                        true if is_item(items, text) => {
+                            let _ = write!(out, "[`{}`](#{})", text, link_target(text));
+                        }
+                        true => {
+                            let _ = write!(out, "`{}`", text);
+                        }
+                        false => out.push_str(text),
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        if !item.calls.is_empty() {
+            let calls: Vec<_> = item.calls.iter().map(|c| format!("[`{}`](#{})", c, c)).collect();
+            let _ = writeln!(out, "\nCalls {}.", calls.join(", "));
+        }
+    }
+    out
+}
+
+/// Renders the items as a standalone HTML page, with an index of them at the top
+pub fn html(title: &str, items: &[Item]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>\n<style>{}</style>\n</head>\n<body>", escape(title), STYLE);
+    let _ = writeln!(out, "<h1>{}</h1>", escape(title));
+    if items.is_empty() {
+        let _ = writeln!(out, "<p>Nothing here is documented yet.</p>");
+    } else {
+        let _ = writeln!(out, "<ul class=\"index\">");
+        for item in items {
+            let _ = writeln!(out, "<li><a href=\"#{}\"><code>{}</code></a></li>", item.name, escape(&item.name));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    for item in items {
+        let kind = match item.kind {
+            ItemKind::Proc => "proc",
+            ItemKind::Const => "const",
+        };
+        let _ = writeln!(out, "<section class=\"{}\" id=\"{}\">", kind, item.name);
+        let _ = writeln!(out, "<h2><a href=\"#{}\">{}</a></h2>", item.name, escape(&item.name));
+        let _ = writeln!(out, "<pre class=\"signature\"><code>{}</code></pre>", escape(&item.signature));
+        docs_html(&mut out, items, &item.docs);
+        if !item.calls.is_empty() {
+            let calls: Vec<_> = item
+                .calls
+                .iter()
+                .map(|c| format!("<a href=\"#{}\"><code>{}</code></a>", c, escape(c)))
+                .collect();
+            let _ = writeln!(out, "<p class=\"calls\">Calls {}.</p>", calls.join(", "));
+        }
+        let _ = writeln!(out, "</section>");
+    }
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+const STYLE: &str = "\
+body { max-width: 50em; margin: 2em auto; padding: 0 1em; font-family: sans-serif; line-height: 1.5; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+h2 a { color: inherit; text-decoration: none; }
+section { border-top: 1px solid #ddd; }";
+
+/// The paragraphs and code blocks of some docs, as HTML
+fn docs_html(out: &mut String, items: &[Item], docs: &str) {
+    let mut paragraph = vec![];
+    let mut code: Option<Vec<&str>> = None;
+    for line in docs.lines() {
+        if is_fence(line) {
+            match code.take() {
+                Some(lines) => code_html(out, &lines),
+                None => {
+                    paragraph_html(out, items, &mut paragraph);
+                    code = Some(vec![]);
+                }
+            }
+        } else if let Some(lines) = &mut code {
+            lines.push(line);
+        } else if line.trim().is_empty() {
+            paragraph_html(out, items, &mut paragraph);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    // an unclosed code block runs to the end of the docs
+    if let Some(lines) = code {
+        code_html(out, &lines);
+    }
+    paragraph_html(out, items, &mut paragraph);
+}
+
+fn code_html(out: &mut String, lines: &[&str]) {
+    let _ = writeln!(out, "<pre><code>{}</code></pre>", escape(&lines.join("\n")));
+}
+
+fn paragraph_html(out: &mut String, items: &[Item], lines: &mut Vec<&str>) {
+    if lines.is_empty() {
+        return;
+    }
+    out.push_str("<p>");
+    for (code, text) in code_spans(&lines.join("\n")) {
+        match code {
+            true if is_item(items, text) => {
+                let _ = write!(out, "<a href=\"#{}\"><code>{}</code></a>", link_target(text), escape(text));
+            }
+            true => {
+                let _ = write!(out, "<code>{}</code>", escape(text));
+            }
+            false => out.push_str(&escape(text)),
+        }
+    }
+    out.push_str("</p>\n");
+    lines.clear();
+}
+
+/// Splits text into plain text and inline code, which is marked `true`
+///
+/// A backtick without a partner is left in the plain text.
+fn code_spans(text: &str) -> Vec<(bool, &str)> {
+    let mut spans = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('`') {
+        let end = match rest[start + 1..].find('`') {
+            Some(end) => start + 1 + end,
+            None => break,
+        };
+        if start > 0 {
+            spans.push((false, &rest[..start]));
+        }
+        spans.push((true, &rest[start + 1..end]));
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        spans.push((false, rest));
+    }
+    spans
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+/// The item inline code refers to, ignoring the brackets of a call
+fn link_target(code: &str) -> &str {
+    code.strip_suffix("()").unwrap_or(code)
+}
+
+fn is_item(items: &[Item], code: &str) -> bool {
+    items.iter().any(|i| i.name == link_target(code))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocTestMode {
+    /// Compiled and run, passing if main returns 0
+    Run,
+    /// Only compiled, from a block marked `no_run`
+    NoRun,
+    /// Skipped, from a block marked `ignore`
+    Ignore,
+}
+
+/// A code block from some docs, made into a program of its own
+#[derive(Debug, Clone)]
+pub struct DocTest {
+    /// The documented item, along with the line the block starts on
+    pub name: String,
+    pub mode: DocTestMode,
+    pub source: String,
+}
+
+/// Makes a doc-test of each Elgin code block in the docs of `ast`, which was parsed from `file`
+///
+/// A block is Elgin code when its fence has no info string, or only the
+/// tags `elgin`, `no_run` and `ignore`. Each test gets every top level
+/// statement of the file apart from main, and then the block itself. A
+/// block without its own main is wrapped in `proc main(): i32`, which
+/// returns 0 once the end of the block is reached, so returning anything
+/// else early fails the test.
+pub fn tests(file: &SourceFile, ast: &[Span<Node>]) -> Vec<DocTest> {
+    let mut prelude = String::new();
+    for node in ast {
+        match &node.contents {
+            Node::ProcStatement { name, .. } if name == "main" => (),
+            _ => {
+                prelude.push_str(&file.source()[node.pos..node.pos + node.len]);
+                prelude.push_str("\n\n");
+            }
+        }
+    }
+
+    let mut tests = vec![];
+    for item in collect(ast) {
+        let lines = doc_lines(file, item.pos);
+        let mut block: Option<(usize, Option<DocTestMode>, Vec<&str>)> = None;
+        for (i, line) in item.docs.lines().enumerate() {
+            if !is_fence(line) {
+                if let Some((_, _, code)) = &mut block {
+                    code.push(line);
+                }
+                continue;
+            }
+            match block.take() {
+                Some((start, mode, code)) => {
+                    if let Some(mode) = mode {
+                        tests.push(DocTest {
+                            name: format!("{} (line {})", item.name, start),
+                            mode,
+                            source: format!("{}{}", prelude, test_main(&code)),
+                        });
+                    }
+                }
+                None => {
+                    let start = lines.get(i).copied().unwrap_or(0);
+                    block = Some((start, test_mode(line), vec![]));
+                }
+            }
+        }
+    }
+    tests
+}
+
+/// What a code fence's info string makes of its block, or `None` if it isn't Elgin
+fn test_mode(fence: &str) -> Option<DocTestMode> {
+    let mut mode = DocTestMode::Run;
+    for tag in fence.trim_start().trim_start_matches('`').split(|c: char| c == ',' || c.is_whitespace()) {
+        match tag {
+            "" | "elgin" => (),
+            "no_run" => mode = DocTestMode::NoRun,
+            "ignore" => mode = DocTestMode::Ignore,
+            _ => return None,
+        }
+    }
+    Some(mode)
+}
+
+/// A doc-test's main proc, which is the block itself unless it needs wrapping
+fn test_main(code: &[&str]) -> String {
+    if code.iter().any(|line| line.trim_start().starts_with("proc main(")) {
+        return code.iter().map(|line| format!("{}\n", line)).collect();
+    }
+    let mut main = String::from("proc main(): i32 {\n");
+    for line in code {
+        let _ = writeln!(main, "    {}", line);
+    }
+    main.push_str("    return 0\n}\n");
+    main
+}
+
+/// The line number of each line of the docs before the declaration at `pos`
+///
+/// The parser lets blank lines come between doc comments, so those are skipped.
+fn doc_lines(file: &SourceFile, pos: usize) -> Vec<usize> {
+    let (decl, _) = file.line_col(pos);
+    let mut lines = vec![];
+    for line in (1..decl).rev() {
+        let text = file.line(line).trim();
+        if text.starts_with("#:") {
+            lines.push(line);
+        } else if !text.is_empty() {
+            break;
+        }
+    }
+    lines.reverse();
+    lines
+}
+
+/// Appends the names of the procs called anywhere in `node`
+fn called_procs(node: &Span<Node>, calls: &mut Vec<String>) {
+    if let Node::Call { name, .. } = &node.contents {
+        if !calls.contains(name) {
+            calls.push(name.clone());
+        }
+    }
+    for child in node.contents.children() {
+        called_procs(child, calls);
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
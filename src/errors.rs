@@ -2,12 +2,17 @@
 
 use ErrorType::*;
 
+use crate::lints::{Lint, LintLevel};
+use crate::source_map::FileId;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
-use std::sync::Mutex;
 
 #[derive(Clone)]
 pub struct Span<T: fmt::Debug> {
     pub contents: T,
+    pub file: FileId,
     pub pos: usize,
     pub len: usize,
 }
@@ -22,52 +27,218 @@ impl<T: fmt::Debug> fmt::Debug for Span<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum ErrorType {
     SyntaxError,
-    //TypeError,
+    TypeError,
     NameError,
+    InternalError,
+    Lint,
+    TypeInference,
 }
 
-#[derive(Debug)]
-pub struct Error {
-    typ: ErrorType,
-    msg: String,
-    pos: usize,
-    len: usize,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
 }
 
-pub struct Logger {
-
+/// Extra source location shown alongside the main one of an error, in the same file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub msg: String,
+    pub pos: usize,
+    pub len: usize,
 }
 
+/// A replacement for part of the source which would fix an error, in the same file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub msg: String,
+    pub pos: usize,
+    pub len: usize,
+    pub replacement: String,
+}
 
-lazy_static! {
-    pub static ref ERRORS: Mutex<Vec<Error>> = Mutex::new(vec![]);
+#[derive(Debug, PartialEq)]
+pub struct Error {
+    pub typ: ErrorType,
+    pub severity: Severity,
+    pub lint: Option<&'static str>,
+    pub code: Option<&'static str>,
+    pub msg: String,
+    pub file: FileId,
+    pub pos: usize,
+    pub len: usize,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    pub suggestions: Vec<Suggestion>,
 }
 
-impl Logger {
-    pub fn log(typ: ErrorType, msg: &str, pos: usize, len: usize) {
-        ERRORS.lock().unwrap().push(Error {
+impl Error {
+    pub fn new(typ: ErrorType, msg: &str, file: FileId, pos: usize, len: usize) -> Self {
+        Error {
             typ,
+            severity: Severity::Error,
+            lint: None,
+            code: None,
+            msg: msg.to_owned(),
+            file,
+            pos,
+            len,
+            labels: vec![],
+            notes: vec![],
+            help: None,
+            suggestions: vec![],
+        }
+    }
+
+    /// Gives the error a code which `elgin explain` knows about
+    pub fn code(mut self, code: &'static str) -> Self {
+        debug_assert!(crate::codes::find(code).is_some(), "no explanation for {}", code);
+        self.code = Some(code);
+        self
+    }
+
+    pub fn label(mut self, msg: &str, pos: usize, len: usize) -> Self {
+        self.labels.push(Label {
             msg: msg.to_owned(),
             pos,
             len,
         });
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_owned());
+        self
+    }
+
+    /// Suggests replacing `len` characters at `pos` with `replacement`
+    pub fn suggest(mut self, msg: &str, pos: usize, len: usize, replacement: &str) -> Self {
+        self.suggestions.push(Suggestion {
+            msg: msg.to_owned(),
+            pos,
+            len,
+            replacement: replacement.to_owned(),
+        });
+        self
+    }
+}
+
+/// The errors found while compiling a single file
+///
+/// Each stage of the compiler gets a reference to the same `Diagnostics`,
+/// so separate compilations never see each other's errors.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    errors: RefCell<Vec<Error>>,
+    lint_levels: HashMap<&'static str, LintLevel>,
+    warnings_as_errors: bool,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn log(&self, typ: ErrorType, code: &'static str, msg: &str, file: FileId, pos: usize, len: usize) {
+        self.emit(Error::new(typ, msg, file, pos, len).code(code));
+    }
+
+    /// Logs an error built up with labels, notes or help
+    ///
+    /// An error exactly like one already logged is dropped, since the parser
+    /// can go over the same code twice while recovering from an error.
+    pub fn emit(&self, error: Error) {
+        let mut errors = self.errors.borrow_mut();
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
     }
 
     #[inline]
-    pub fn name_error(msg: &str, pos: usize, len: usize) {
-        Self::log(NameError, msg, pos, len);
+    pub fn type_error(&self, code: &'static str, msg: &str, file: FileId, pos: usize, len: usize) {
+        self.log(TypeError, code, msg, file, pos, len);
     }
 
-    //#[inline]
-    //pub fn type_error(msg: &str, pos: usize, len: usize) {
-    //    Self::log(TypeError, msg, pos, len);
-    //}
+    #[inline]
+    pub fn syntax_error(&self, code: &'static str, msg: &str, file: FileId, pos: usize, len: usize) {
+        self.log(SyntaxError, code, msg, file, pos, len);
+    }
 
+    /// Logs something worth knowing which isn't a problem
+    pub fn note(&self, typ: ErrorType, msg: &str, file: FileId, pos: usize, len: usize) {
+        let mut note = Error::new(typ, msg, file, pos, len);
+        note.severity = Severity::Note;
+        self.emit(note);
+    }
+
+    pub fn set_lint_level(&mut self, lint: &'static Lint, level: LintLevel) {
+        self.lint_levels.insert(lint.name, level);
+    }
+
+    /// Makes every warning an error, for -Werror
+    pub fn deny_warnings(&mut self) {
+        self.warnings_as_errors = true;
+    }
+
+    /// Logs `error` as a warning or an error depending on the level of `lint`
+    pub fn lint(&self, lint: &'static Lint, mut error: Error) {
+        let (level, source) = match self.lint_levels.get(lint.name) {
+            Some(level) => (*level, "requested on the command line"),
+            None => (lint.default, "on by default"),
+        };
+        let flag = match level {
+            LintLevel::Allow => return,
+            LintLevel::Warn => "-W",
+            LintLevel::Deny => "-D",
+        };
+        error.lint = Some(lint.name);
+        error.severity = if level == LintLevel::Deny || self.warnings_as_errors {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        error.notes.push(format!("`{} {}` is {}", flag, lint.name, source));
+        if level == LintLevel::Warn && self.warnings_as_errors {
+            error.notes.push("warnings are treated as errors because of `-Werror`".to_owned());
+        }
+        self.emit(error);
+    }
+
+    /// Reports a bug in the compiler itself rather than in the code being compiled
     #[inline]
-    pub fn syntax_error(msg: &str, pos: usize, len: usize) {
-        Self::log(SyntaxError, msg, pos, len);
+    pub fn internal_error(&self, msg: &str, file: FileId, pos: usize, len: usize) {
+        self.emit(
+            Error::new(InternalError, msg, file, pos, len)
+                .note("this is a bug in elgin, please report it"),
+        );
+    }
+
+    /// Whether anything fatal has been logged, as warnings and notes don't stop compilation
+    pub fn has_errors(&self) -> bool {
+        self.errors.borrow().iter().any(|e| e.severity == Severity::Error)
+    }
+
+    /// The number of errors so far, to be passed to `rollback` when backtracking
+    pub fn snapshot(&self) -> usize {
+        self.errors.borrow().len()
+    }
+
+    /// Forgets every error logged since `snapshot` was taken
+    pub fn rollback(&self, snapshot: usize) {
+        self.errors.borrow_mut().truncate(snapshot);
+    }
+
+    pub fn into_errors(self) -> Vec<Error> {
+        self.errors.into_inner()
     }
 }
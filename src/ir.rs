@@ -2,8 +2,9 @@
 //! Elgin IR is the intermediate representation which is then used for type analysis in analysis.rs
 //! It is then converted into LLVM IR in the codegen phase
 
-use crate::errors::{Logger, Span};
+use crate::errors::{Diagnostics, Error, ErrorType::{NameError, SyntaxError, TypeError}, Span};
 use crate::astgen::Node;
+use crate::source_map::FileId;
 use crate::types::Type;
 
 use std::collections::HashMap;
@@ -14,14 +15,20 @@ type IRResult = Option<Vec<Span<Instruction>>>;
 
 pub struct IRBuilder<'i> {
     ast: &'i [Span<Node>],
+    pub diagnostics: &'i Diagnostics,
     pub available_type_var: usize,
     available_label_id: usize,
     pub scopes: Vec<Scope>,
     pub procs: Vec<IRProc>, 
     pub consts: HashMap<String, Span<Node>>,
-
-    current_loop_entrance_id: usize,
-    current_after_loop_id: usize,
+    pub verbose: bool,
+    /// The file of the proc or const being worked on
+    pub file: FileId,
+
+    /// The entrance and exit labels of each loop around the code being built, innermost last
+    loops: Vec<(usize, usize)>,
+    /// The consts whose values are being inlined, outermost first
+    expanding: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +38,9 @@ pub struct IRProc {
     pub arg_types: Vec<Type>,
     pub ret_type: Type,
     pub body: Vec<Span<Instruction>>,
+    pub file: FileId,
+    pub pos: usize,
+    pub len: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -84,26 +94,30 @@ impl fmt::Debug for Instruction {
     }
 }
 
-pub fn spanned(ins: Instruction, pos: usize, len: usize) -> Span<Instruction> {
-    Span {
-        contents: ins.clone(),
-        pos,
-        len,
-    }
-}
-
 impl<'i> IRBuilder<'i> {
-    pub fn new(ast: &'i [Span<Node>], available_type_var: usize) -> Self {
+    pub fn new(ast: &'i [Span<Node>], available_type_var: usize, diagnostics: &'i Diagnostics) -> Self {
         IRBuilder {
             ast,
+            diagnostics,
             available_type_var,
             available_label_id: 0,
             scopes: vec![],
             procs: vec![],
             consts: HashMap::new(),
+            verbose: false,
+            file: FileId::default(),
+
+            loops: vec![],
+            expanding: vec![],
+        }
+    }
 
-            current_loop_entrance_id: 0,
-            current_after_loop_id: 0,
+    fn spanned(&self, ins: Instruction, pos: usize, len: usize) -> Span<Instruction> {
+        Span {
+            contents: ins,
+            file: self.file,
+            pos,
+            len,
         }
     }
 
@@ -111,11 +125,13 @@ impl<'i> IRBuilder<'i> {
         self.build_header();
         // just declare all functions + constants
         for node in self.ast {
+            self.file = node.file;
             match node.clone().contents {
                 Node::ConstStatement {
                     name,
                     typ,
                     value,
+                    ..
                 } => {
                     self.const_statement(name, typ, value, node.pos, node.len)?;
                 }
@@ -132,13 +148,25 @@ impl<'i> IRBuilder<'i> {
                         arg_types,
                         ret_type,
                         body: vec![],
+                        file: node.file,
+                        pos: node.pos,
+                        len: node.len,
                     });
                 }
+                // already reported by the parser
+                Node::Error => (),
                 n => {
-                    Logger::syntax_error(
-                        format!("A node of type {:?} is not allowed at the top level of a module", n).as_str(),
-                        node.pos,
-                        node.len,
+                    self.diagnostics.emit(
+                        Error::new(
+                            SyntaxError,
+                            format!("A node of type {:?} is not allowed at the top level of a module", n).as_str(),
+                            self.file,
+                            node.pos,
+                            node.len,
+                        )
+                        .code("E0007")
+                        .note("only procs and consts can appear at the top level")
+                        .help("move it into the body of a proc, such as main"),
                     );
                     return None
                 }
@@ -146,11 +174,13 @@ impl<'i> IRBuilder<'i> {
         }
         // then actually generate code
         for node in self.ast {
+            self.file = node.file;
             match node.clone().contents {
                 Node::ConstStatement {
                     name,
                     typ,
                     value,
+                    ..
                 } => {
                     self.const_statement(name, typ, value, node.pos, node.len);
                 }
@@ -160,6 +190,7 @@ impl<'i> IRBuilder<'i> {
                     arg_types,
                     ret_type,
                     body,
+                    ..
                 } => {
                     let pstat = self.proc_statement(
                         name, args, arg_types, ret_type, body, node.pos, node.len,
@@ -172,7 +203,8 @@ impl<'i> IRBuilder<'i> {
                         }
                     }
                 }
-                _ => unreachable!(),
+                // anything else was already reported above
+                _ => (),
             }
         }
         Some(&self.procs)
@@ -185,6 +217,20 @@ impl<'i> IRBuilder<'i> {
             arg_types: vec![Type::Ptr(Box::new(Type::I8))],
             ret_type: Type::I32,
             body: vec![],
+            file: FileId::default(),
+            pos: 0,
+            len: 0,
+        });
+        // writes exactly the bytes given, unlike puts which adds a newline
+        self.procs.push(IRProc {
+            name: "write".to_owned(),
+            args: vec!["fd".to_owned(), "buf".to_owned(), "count".to_owned()],
+            arg_types: vec![Type::I32, Type::Ptr(Box::new(Type::I8)), Type::N64],
+            ret_type: Type::I64,
+            body: vec![],
+            file: FileId::default(),
+            pos: 0,
+            len: 0,
         });
     }
 
@@ -237,7 +283,7 @@ impl<'i> IRBuilder<'i> {
                 value,
             } => self.var_statement(name, typ, value, node.pos, node.len)?,
             ConstStatement { .. } => {
-                Logger::syntax_error("Found const statement not at top level. This feature is NYI.", node.pos, node.len);
+                self.diagnostics.syntax_error("E0008", "Found const statement not at top level. This feature is NYI.", self.file, node.pos, node.len);
                 return None;
             },
             AssignStatement {
@@ -258,7 +304,15 @@ impl<'i> IRBuilder<'i> {
             ContinueStatement {
 
             } => self.continue_statement(node.pos, node.len)?,
-            _ => unreachable!(),
+            ProcStatement { .. } => {
+                self.diagnostics.syntax_error("E0013", "Found proc statement inside another proc", self.file, node.pos, node.len);
+                return None;
+            },
+            Node::Error => vec![],
+            UseStatement { .. } => {
+                self.diagnostics.syntax_error("E0013", "Found use statement inside a proc", self.file, node.pos, node.len);
+                return None;
+            },
         })
     }
 
@@ -269,7 +323,7 @@ impl<'i> IRBuilder<'i> {
         pos: usize,
         len: usize,
     ) -> IRResult {
-        Some(vec![spanned(Instruction {
+        Some(vec![self.spanned(Instruction {
             ins: InstructionType::Push(value),
             typ,
         }, pos, len)])
@@ -282,12 +336,22 @@ impl<'i> IRBuilder<'i> {
         pos: usize,
         len: usize,
     ) -> IRResult {
-        let proc = self.locate_proc(&name)?.clone();
+        let proc = self.locate_proc(&name, pos)?.clone();
+        if args.len() != proc.args.len() {
+            self.diagnostics.type_error(
+                "E0015",
+                format!("{} takes {} arguments, but {} were given", name, proc.args.len(), args.len()).as_str(),
+                self.file,
+                pos,
+                len,
+            );
+            return None;
+        }
         let mut res = vec![];
         for arg in args {
             res.append(&mut self.node(&arg)?);
         }
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Call(proc.name),
             typ: proc.ret_type,
         }, pos, len));
@@ -306,7 +370,8 @@ impl<'i> IRBuilder<'i> {
         res.append(&mut self.node(&left)?);
         res.append(&mut self.node(&right)?);
 
-        res.push(spanned(Instruction {
+        let typ = Type::Variable(self.next_type_var());
+        res.push(self.spanned(Instruction {
             ins: match op.as_str() {
                 "+" => InstructionType::Add(false),
                 "-" => InstructionType::Subtract(false),
@@ -325,9 +390,9 @@ impl<'i> IRBuilder<'i> {
                 "<" => InstructionType::Compare(CompareType::LT),
                 ">=" => InstructionType::Compare(CompareType::GE),
                 "<=" => InstructionType::Compare(CompareType::LE),
-                _ => todo!(),
+                _ => return self.unsupported_op(&op, pos, len),
             },
-            typ: Type::Variable(self.next_type_var()),
+            typ,
         }, pos, len));
         Some(res)
     }
@@ -339,15 +404,22 @@ impl<'i> IRBuilder<'i> {
         pos: usize,
         len: usize,
     ) -> IRResult {
+        if let (Node::Literal { typ, value }, "-" | "-~") = (&right.contents, op.as_str()) {
+            if matches!(typ, Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::I128) {
+                // negated in place, as a signed type's minimum doesn't fit before it's negated
+                return self.literal(typ.clone(), format!("-{}", value), pos, len);
+            }
+        }
         let mut res = vec![];
         res.append(&mut self.node(&right)?);
-        res.push(spanned(Instruction {
+        let typ = Type::Variable(self.next_type_var());
+        res.push(self.spanned(Instruction {
             ins: match op.as_str() {
                 "-" => InstructionType::Negate(false),
                 "-~" => InstructionType::Negate(true),
-                _ => todo!(),
+                _ => return self.unsupported_op(&op, pos, len),
             },
-            typ: Type::Variable(self.next_type_var()),
+            typ,
         }, pos, len));
         Some(res)
     }
@@ -359,7 +431,13 @@ impl<'i> IRBuilder<'i> {
         pos: usize,
         len: usize,
     ) -> IRResult {
-        todo!("{:?} {:?} {:?} {:?}", op, left, pos, len);
+        self.node(&left)?;
+        self.unsupported_op(&op, pos, len)
+    }
+
+    fn unsupported_op(&self, op: &str, pos: usize, len: usize) -> IRResult {
+        self.diagnostics.syntax_error("E0014", format!("The operator {} isn't supported yet", op).as_str(), self.file, pos, len);
+        None
     }
 
     fn index_op(
@@ -373,21 +451,47 @@ impl<'i> IRBuilder<'i> {
         let mut obj = self.node(&object)?;
         ins.append(&mut obj); 
         ins.append(&mut self.node(&index)?);
-        ins.push(spanned(Instruction {
+        let typ = Type::Variable(self.next_type_var());
+        ins.push(self.spanned(Instruction {
             ins: InstructionType::Index,
-            typ: Type::Variable(self.next_type_var()),
+            typ,
         }, pos, len));
         Some(ins)
     }
 
     fn variable_ref(&mut self, name: String, pos: usize, len: usize) -> IRResult {
         if self.consts.contains_key(&name) {
-            let constant = self.consts[&name].clone();
-            return self.node(&constant);
+            if let Some(start) = self.expanding.iter().position(|c| *c == name) {
+                let mut cycle = self.expanding[start..].to_vec();
+                cycle.push(name.clone());
+                self.diagnostics.emit(
+                    Error::new(
+                        NameError,
+                        format!("The const {} is defined in terms of itself", name).as_str(),
+                        self.file,
+                        pos,
+                        len,
+                    )
+                    .code("E0027")
+                    .note(format!("the cycle is {}", cycle.join(" -> ")).as_str()),
+                );
+                return None;
+            }
+            // the constant's value is inlined where it's used
+            let constant = Span {
+                file: self.file,
+                pos,
+                len,
+                ..self.consts[&name].clone()
+            };
+            self.expanding.push(name);
+            let inlined = self.node(&constant);
+            self.expanding.pop();
+            return inlined;
         }
 
-        let typ = self.locate_var(&name)?;
-        Some(vec![spanned(Instruction {
+        let typ = self.locate_var(&name, pos)?;
+        Some(vec![self.spanned(Instruction {
             ins: InstructionType::Load(name),
             typ,
         }, pos, len)])
@@ -408,36 +512,36 @@ impl<'i> IRBuilder<'i> {
         let mut blocks_ending_in_return = 2;
 
         res.append(&mut self.node(&condition)?);
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Branch(body_label, else_label),
             typ: Type::NoReturn,
-        }, pos, len));
-        res.push(spanned(Instruction {
+        }, condition.pos, condition.len));
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Label(body_label),
             typ: Type::Undefined,
         }, pos, len));
         res.append(&mut self.node(&body)?);
         if res.last().unwrap().contents.ins != InstructionType::Return {
             blocks_ending_in_return -= 1;
-            res.push(spanned(Instruction {
+            res.push(self.spanned(Instruction {
                 ins: InstructionType::Jump(end_label),
                 typ: Type::Undefined,
             }, pos, len));
         }
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Label(else_label),
             typ: Type::Undefined,
         }, pos, len));
         res.append(&mut self.node(&else_body)?);
         if res.last().unwrap().contents.ins != InstructionType::Return {
             blocks_ending_in_return -= 1;
-            res.push(spanned(Instruction {
+            res.push(self.spanned(Instruction {
                 ins: InstructionType::Jump(end_label),
                 typ: Type::Undefined,
             }, pos, len));
         }
         if blocks_ending_in_return < 2 {
-            res.push(spanned(Instruction {
+            res.push(self.spanned(Instruction {
                 ins: InstructionType::Label(end_label),
                 typ: Type::Undefined,
             }, pos, len));
@@ -454,39 +558,40 @@ impl<'i> IRBuilder<'i> {
     ) -> IRResult {
         let mut res = vec![];
         let cond_label = self.next_label_id();
-        self.current_loop_entrance_id = cond_label;
         let body_label = self.next_label_id();
         let end_label = self.next_label_id();
-        self.current_after_loop_id = end_label;
         let mut blocks_ending_in_return = 1;
 
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Jump(cond_label),
             typ: Type::Undefined,
         }, pos, len));
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Label(cond_label),
             typ: Type::Undefined,
         }, pos, len));
         res.append(&mut self.node(&condition)?);
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Branch(body_label, end_label),
             typ: Type::NoReturn,
-        }, pos, len));
-        res.push(spanned(Instruction {
+        }, condition.pos, condition.len));
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Label(body_label),
             typ: Type::Undefined,
         }, pos, len));
-        res.append(&mut self.node(&body)?);
+        self.loops.push((cond_label, end_label));
+        let body = self.node(&body);
+        self.loops.pop();
+        res.append(&mut body?);
         if res.last().unwrap().contents.ins != InstructionType::Return {
             blocks_ending_in_return -= 1;
-            res.push(spanned(Instruction {
+            res.push(self.spanned(Instruction {
                 ins: InstructionType::Jump(cond_label),
                 typ: Type::Undefined,
             }, pos, len));
         }
         if blocks_ending_in_return < 2 {
-            res.push(spanned(Instruction {
+            res.push(self.spanned(Instruction {
                 ins: InstructionType::Label(end_label),
                 typ: Type::Undefined,
             }, pos, len));
@@ -515,7 +620,7 @@ impl<'i> IRBuilder<'i> {
             .unwrap()
             .insert(name.clone(), typ.clone());
         let mut res = self.node(&value)?;
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Allocate(name.clone()),
             typ,
         }, pos, len));
@@ -530,9 +635,9 @@ impl<'i> IRBuilder<'i> {
         len: usize,
     ) -> IRResult {
         let mut res = self.node(&value)?;
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::Store(name.clone()),
-            typ: self.locate_var(&name)?,
+            typ: self.locate_var(&name, pos)?,
         }, pos, len));
         Some(res)
     }
@@ -547,9 +652,9 @@ impl<'i> IRBuilder<'i> {
     ) -> IRResult {
         let mut res = self.node(&value)?;
         res.append(&mut self.node(&index)?);
-        res.push(spanned(Instruction {
+        res.push(self.spanned(Instruction {
             ins: InstructionType::StoreIndexed(name.clone()),
-            typ: self.locate_var(&name)?,
+            typ: self.locate_var(&name, pos)?,
         }, pos, len));
         Some(res)
     }
@@ -562,7 +667,7 @@ impl<'i> IRBuilder<'i> {
     ) -> IRResult {
         let mut res = self.node(&val)?;
         res.push(
-            spanned(Instruction {
+            self.spanned(Instruction {
                 ins: InstructionType::Return,
                 typ: res.last().unwrap().clone().contents.typ,
             }, pos, len)
@@ -571,23 +676,43 @@ impl<'i> IRBuilder<'i> {
     }
 
     fn break_statement(&mut self, pos: usize, len: usize) -> IRResult {
+        let (_, exit) = self.enclosing_loop("break", pos, len)?;
         Some(vec![
-            spanned(Instruction {
-                ins: InstructionType::Jump(self.current_after_loop_id),
+            self.spanned(Instruction {
+                ins: InstructionType::Jump(exit),
                 typ: Type::NoReturn,
             }, pos, len)
         ])
     }
 
     fn continue_statement(&mut self, pos: usize, len: usize) -> IRResult {
+        let (entrance, _) = self.enclosing_loop("continue", pos, len)?;
         Some(vec![
-            spanned(Instruction {
-                ins: InstructionType::Jump(self.current_loop_entrance_id),
+            self.spanned(Instruction {
+                ins: InstructionType::Jump(entrance),
                 typ: Type::NoReturn,
             }, pos, len)
         ])
     }
 
+    /// The labels of the innermost loop, which `keyword` at `pos` has to be inside
+    fn enclosing_loop(&self, keyword: &str, pos: usize, len: usize) -> Option<(usize, usize)> {
+        if self.loops.is_empty() {
+            self.diagnostics.emit(
+                Error::new(
+                    SyntaxError,
+                    format!("`{}` outside of a loop", keyword).as_str(),
+                    self.file,
+                    pos,
+                    len,
+                )
+                .code("E0022")
+                .help(format!("`{}` can only be used inside a `while` or `loop` body", keyword).as_str()),
+            );
+        }
+        self.loops.last().copied()
+    }
+
     fn const_statement(
         &mut self,
         name: String,
@@ -611,26 +736,41 @@ impl<'i> IRBuilder<'i> {
         pos: usize,
         len: usize,
     ) -> Option<IRProc> {
-        let mut ins = vec![];
         self.scopes.push(HashMap::new());
         let scope = self.scopes.last_mut().unwrap();
         for (i, arg) in args.iter().enumerate() {
             let t = arg_types[i].clone();
             scope.insert(arg.clone(), t);
         }
-        if let Node::Block { nodes, .. } = body.contents {
-            for node in &nodes {
-                ins.append(&mut self.node(&node)?);
-            }
+        if let Node::Block { nodes, .. } = &body.contents {
+            let built: Option<Vec<_>> = nodes.iter().map(|node| self.node(node)).collect();
+            // the proc's variables can't be seen from the next one
+            self.scopes.pop();
+            let mut ins = built?.concat();
             if ret_type == Type::Undefined && nodes.len() > 0 {
-                ins.push(spanned(Instruction {
+                // the implicit return happens at the closing brace
+                let end = (body.pos + body.len).saturating_sub(1);
+                ins.push(self.spanned(Instruction {
                     ins: InstructionType::Push("undefined".to_owned()),
                     typ: Type::Undefined,
-                }, pos, len));
-                ins.push(spanned(Instruction {
+                }, end, 1));
+                ins.push(self.spanned(Instruction {
                     ins: InstructionType::Return,
                     typ: Type::Undefined,
-                }, pos, len));
+                }, end, 1));
+            } else if ret_type != Type::Undefined && reaches_end(&ins) {
+                self.diagnostics.emit(
+                    Error::new(
+                        TypeError,
+                        format!("{} can reach its end without returning a value", name).as_str(),
+                        self.file,
+                        pos,
+                        len,
+                    )
+                    .code("E0026")
+                    .help("add a `return` at the end of the proc"),
+                );
+                return None;
             }
             Some(IRProc {
                 name,
@@ -638,9 +778,14 @@ impl<'i> IRBuilder<'i> {
                 arg_types,
                 ret_type,
                 body: ins,
+                file: self.file,
+                pos,
+                len,
             })
         } else {
-            panic!()
+            self.scopes.pop();
+            self.diagnostics.internal_error("The body of a proc isn't a block", self.file, body.pos, body.len);
+            None
         }
     }
 
@@ -654,7 +799,8 @@ impl<'i> IRBuilder<'i> {
         self.available_label_id - 1
     }
 
-    pub fn locate_var(&self, name: &String) -> Option<Type> {
+    /// Finds the type of the variable `name`, which is used at `pos`
+    pub fn locate_var(&self, name: &String, pos: usize) -> Option<Type> {
         //let mut scope_index = self.scopes.len() - 1;
         //while scope_index >= 0 {
         for scope in self.scopes.iter().rev() {
@@ -667,23 +813,103 @@ impl<'i> IRBuilder<'i> {
             //scope_index -= 1
         }
 
-        Logger::name_error(
+        let mut error = Error::new(
+            NameError,
             format!("Can't find a variable named {} in the current scope", name).as_str(),
-            0, 0,
-        );
+            self.file,
+            pos,
+            name.len(),
+        )
+        .code("E0009");
+        let vars = self.scopes.iter().flat_map(|scope| scope.keys()).map(|v| (v, "variable"));
+        let consts = self.consts.keys().map(|c| (c, "const"));
+        if let Some((similar, kind)) = closest(name, vars.chain(consts)) {
+            error = error.suggest(
+                format!("a {} with a similar name exists: `{}`", kind, similar).as_str(),
+                pos,
+                name.len(),
+                similar,
+            );
+        }
+        self.diagnostics.emit(error);
         None
     }
 
-    pub fn locate_proc(&self, name: &String) -> Option<&IRProc> {
+    /// Finds the proc `name`, which is called at `pos`
+    pub fn locate_proc(&self, name: &String, pos: usize) -> Option<&IRProc> {
         for proc in &self.procs {
             if proc.name == *name {
                 return Some(proc);
             }
         }
-        Logger::name_error(
+        let mut error = Error::new(
+            NameError,
             format!("Can't find a procedure named {} in the current module", name).as_str(),
-            0, 0,
-        );
+            self.file,
+            pos,
+            name.len(),
+        )
+        .code("E0010");
+        if let Some((similar, _)) = closest(name, self.procs.iter().map(|p| (&p.name, "proc"))) {
+            error = error.suggest(
+                format!("a proc with a similar name exists: `{}`", similar).as_str(),
+                pos,
+                name.len(),
+                similar,
+            );
+        }
+        self.diagnostics.emit(error);
         None
     }
 }
+
+/// Whether running a proc's body can get to the end of it without a return, the same way
+/// codegen splits it into basic blocks: a jump or return ends a block, and a label starts one
+fn reaches_end(body: &[Span<Instruction>]) -> bool {
+    let mut terminated = false;
+    for ins in body {
+        match ins.contents.ins {
+            InstructionType::Label(_) => terminated = false,
+            InstructionType::Return | InstructionType::Jump(_) | InstructionType::Branch(_, _) => terminated = true,
+            _ => (),
+        }
+    }
+    !terminated
+}
+
+/// The candidate closest to `name`, if any is close enough to be a likely typo
+fn closest<'a, I>(name: &str, candidates: I) -> Option<(&'a str, &'static str)>
+where
+    I: Iterator<Item = (&'a String, &'static str)>,
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|(candidate, kind)| (edit_distance(name, candidate), candidate, kind))
+        .filter(|(distance, _, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, candidate, kind)| (candidate.as_str(), kind))
+}
+
+/// The edit distance between `a` and `b`, where swapping two neighbouring
+/// characters counts as a single edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
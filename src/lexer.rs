@@ -2,16 +2,24 @@
 
 use std::fmt;
 
-use crate::errors::{Logger, Span};
+use unicode_xid::UnicodeXID;
+
+use crate::errors::{Diagnostics, Error, ErrorType::SyntaxError, Span};
+use crate::source_map::FileId;
+use crate::types::Type;
 
 const SPECIAL_CHARS: [char; 9] = ['(', ')', '[', ']', '{', '}', ',', '=', ':'];
 
+/// Every operator, longest first so that the longest match wins
+const OPERATORS: [&str; 15] = ["+~", "-~", "*~", "==", "!=", ">=", "<=", "//", "+", "-", "*", "/", ">", "<", "!"];
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
-    // literals
-    IntLiteral(String),
-    FloatLiteral(String),
+    // literals, in decimal with the type of their suffix, if any
+    IntLiteral(String, Option<Type>),
+    FloatLiteral(String, Option<Type>),
     StrLiteral(String),
+    CharLiteral(char),
 
     // identifier
     Ident(String),
@@ -61,94 +69,343 @@ impl fmt::Display for Token {
 }
 
 pub struct Lexer<'l> {
-    code: &'l [char],
+    code: &'l str,
+    file: FileId,
+    diagnostics: &'l Diagnostics,
+    /// The byte offset of the next character
     index: usize,
     nesting: usize,
 }
 
 impl<'l> Lexer<'l> {
-    pub fn new(code: &'l [char]) -> Self {
+    pub fn new(code: &'l str, file: FileId, diagnostics: &'l Diagnostics) -> Self {
         Lexer {
             code,
+            file,
+            diagnostics,
             index: 0,
             nesting: 0,
         }
     }
 
     fn peek(&self) -> char {
-        if self.index >= self.code.len() {
-            return '\0';
-        }
-        self.code[self.index]
+        self.code[self.index..].chars().next().unwrap_or('\0')
+    }
+
+    /// The character after the next one, or `\0` past the end of the file
+    fn peek_next(&self) -> char {
+        self.code[self.index..].chars().nth(1).unwrap_or('\0')
     }
 
     fn next(&mut self) -> char {
-        self.index += 1;
-        if self.index >= self.code.len() {
-            return '\0';
+        let ch = self.peek();
+        if self.index < self.code.len() {
+            self.index += ch.len_utf8();
         }
-        let ch = self.code[self.index - 1];
         ch
     }
 
-    fn ident_str(&mut self) -> String {
-        let mut ident = String::new();
+    fn ident_str(&mut self) -> &'l str {
+        let start = self.index;
         while is_ident(self.peek()) {
-            ident.push(self.next());
+            self.next();
         }
-        ident
+        &self.code[start..self.index]
     }
 
-    fn number(&mut self) -> Token {
+    /// A number such as `1_000`, `0xFF`, `1.5e-9` or `255n8`, which can be the magnitude of a
+    /// signed type's minimum if it's `negated`
+    fn number(&mut self, negated: bool) -> Token {
+        let start = self.index;
+        let radix = match (self.peek(), self.peek_next()) {
+            ('0', 'x') => 16,
+            ('0', 'o') => 8,
+            ('0', 'b') => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.next();
+            self.next();
+        }
+
         let mut number = String::new();
-        let mut decimal_passed = false;
+        let mut float = false;
+        let mut exponent = false;
+        loop {
+            match self.peek() {
+                '_' => {
+                    self.next();
+                }
+                ch if ch.is_digit(radix) => number.push(self.next()),
+                '.' if radix == 10 && !float => {
+                    float = true;
+                    number.push(self.next());
+                }
+                'e' | 'E' if radix == 10 && !exponent => {
+                    float = true;
+                    exponent = true;
+                    number.push(self.next());
+                    if self.peek() == '+' || self.peek() == '-' {
+                        number.push(self.next());
+                    }
+                }
+                _ => break,
+            }
+        }
+        let suffix = self.suffix_str();
 
-        while is_number(self.peek(), decimal_passed) {
-            number.push(match self.next() {
-                '.' => {
-                    decimal_passed = true;
-                    '.'
+        let error = if number.is_empty() || (float && number.parse::<f64>().is_err()) {
+            Some(format!("Expected digits in the number literal {}", &self.code[start..self.index]))
+        } else if suffix.starts_with(|c: char| c.is_ascii_digit()) {
+            Some(format!("{} isn't a digit in base {}", &suffix[..1], radix))
+        } else {
+            None
+        };
+        if let Some(msg) = error {
+            return self.invalid_number(&msg, start, float);
+        }
+        let typ = match suffix {
+            "" => None,
+            suffix => match suffix_type(suffix) {
+                Some(typ) => Some(typ),
+                None => {
+                    let msg = format!("Unknown suffix {} on a number literal", suffix);
+                    return self.invalid_number(&msg, start, float);
                 }
-                c => c,
-            });
+            },
+        };
+        let float_type = matches!(typ, Some(Type::F32) | Some(Type::F64) | Some(Type::F128));
+
+        if float || float_type {
+            let msg = match typ.clone() {
+                _ if radix != 10 => "A float literal has to be written in base 10".to_owned(),
+                Some(typ) if !float_type => format!("A float literal can't have the integer type {:?}", typ),
+                _ => return Token::FloatLiteral(number, typ),
+            };
+            return self.invalid_number(&msg, start, true);
+        }
+        match u128::from_str_radix(&number, radix) {
+            Ok(value) if typ.as_ref().is_none_or(|t| value <= max_value(t, negated)) => {
+                Token::IntLiteral(value.to_string(), typ)
+            }
+            _ => {
+                let msg = match &typ {
+                    Some(typ) => format!("The number literal {} doesn't fit in an {:?}", &self.code[start..self.index], typ),
+                    None => format!("The number literal {} doesn't fit in 128 bits", &self.code[start..self.index]),
+                };
+                self.invalid_number(&msg, start, false)
+            }
+        }
+    }
+
+    /// Reads the letters and digits straight after a number, which should be its type
+    fn suffix_str(&mut self) -> &'l str {
+        let start = self.index;
+        while is_ident(self.peek()) {
+            self.next();
         }
-        if decimal_passed {
-            Token::FloatLiteral(number)
+        &self.code[start..self.index]
+    }
+
+    /// Reports a malformed number, which is replaced with a zero to carry on
+    fn invalid_number(&mut self, msg: &str, start: usize, float: bool) -> Token {
+        while is_ident(self.peek()) {
+            self.next();
+        }
+        self.diagnostics.emit(
+            Error::new(SyntaxError, msg, self.file, start, self.index - start)
+                .code("E0019"),
+        );
+        if float {
+            Token::FloatLiteral("0".to_owned(), None)
         } else {
-            Token::IntLiteral(number)
+            Token::IntLiteral("0".to_owned(), None)
         }
     }
 
+    /// The longest operator which comes next, or a run of punctuation which is reported
     fn operator(&mut self) -> Token {
-        let mut op = String::new();
-        while is_op(self.peek()) {
-            op.push(self.next());
+        if let Some(op) = self.known_operator() {
+            self.index += op.len();
+            return Token::Op(op.to_owned());
+        }
+        let start = self.index;
+        self.next();
+        while is_op(self.peek()) && self.known_operator().is_none() {
+            self.next();
         }
-        Token::Op(op)
+        self.diagnostics.emit(
+            Error::new(
+                SyntaxError,
+                format!("Unknown operator `{}`", &self.code[start..self.index]).as_str(),
+                self.file,
+                start,
+                self.index - start,
+            )
+            .code("E0020")
+            .help(format!("the operators are {}", OPERATORS.join(" ")).as_str()),
+        );
+        // the parser carries on as if it were a binary operator
+        Token::Op(self.code[start..self.index].to_owned())
+    }
+
+    /// The operator starting at the next character, if there is one
+    fn known_operator(&self) -> Option<&'static str> {
+        OPERATORS.iter().copied().find(|op| self.code[self.index..].starts_with(op))
     }
 
     fn string(&mut self) -> Option<Token> {
         let mut string = String::new();
+        let start = self.index;
         self.next(); // skip "
         while self.peek() != '"' {
-            if self.peek() == '\0' {
-                Logger::syntax_error("Encountered end of file while parsing string literal", self.index, string.len());
+            if self.index >= self.code.len() {
+                self.unterminated_string(start, "\"");
                 return None
             }
-            string.push(self.next());
+            if self.peek() == '\\' {
+                string.extend(self.escape());
+            } else {
+                string.push(self.next());
+            }
         }
         self.next(); // skip "
         Some(Token::StrLiteral(string))
     }
 
-    fn special(&mut self) -> Token {
+    /// Whether a raw string such as `r"C:\"` or `r#"say "hi""#` starts here
+    fn is_raw_string(&self) -> bool {
+        self.code[self.index..].starts_with('r')
+            && self.code[self.index + 1..].trim_start_matches('#').starts_with('"')
+    }
+
+    /// A string without escapes, which ends at a `"` followed by as many `#`s as it started with
+    fn raw_string(&mut self) -> Option<Token> {
+        let start = self.index;
+        self.next(); // skip r
+        let mut terminator = String::from("\"");
+        while self.peek() == '#' {
+            terminator.push(self.next());
+        }
+        self.next(); // skip "
+        match self.code[self.index..].find(&terminator) {
+            Some(len) => {
+                let string = self.code[self.index..self.index + len].to_owned();
+                self.index += len + terminator.len();
+                Some(Token::StrLiteral(string))
+            }
+            None => {
+                self.index = self.code.len();
+                self.unterminated_string(start, &terminator);
+                None
+            }
+        }
+    }
+
+    fn unterminated_string(&self, start: usize, terminator: &str) {
+        self.diagnostics.emit(
+            Error::new(SyntaxError, "Encountered end of file while parsing string literal", self.file, start, self.index - start)
+                .code("E0001")
+                .suggest(format!("add a closing `{}` to end the string", terminator).as_str(), self.index, 0, terminator),
+        );
+    }
+
+    /// A single ASCII character between `'`s, which is an n8
+    fn char_literal(&mut self) -> Token {
+        let start = self.index;
+        self.next(); // skip '
+        let mut chars = vec![];
+        let mut valid = true;
+        while self.peek() != '\'' && self.peek() != '\n' && self.index < self.code.len() {
+            if self.peek() == '\\' {
+                match self.escape() {
+                    Some(ch) => chars.push(ch),
+                    None => valid = false,
+                }
+            } else {
+                chars.push(self.next());
+            }
+        }
+        let closed = self.peek() == '\'';
+        if closed {
+            self.next();
+        }
+        let msg = match chars.as_slice() {
+            _ if !closed => "Expected a `'` to end the character literal",
+            [ch] if ch.is_ascii() => return Token::CharLiteral(*ch),
+            [_] => "A character literal has to be ASCII to fit in an n8",
+            _ => "A character literal has to hold exactly one character",
+        };
+        if valid {
+            self.diagnostics.syntax_error("E0018", msg, self.file, start, self.index - start);
+        }
+        Token::CharLiteral('\0')
+    }
+
+    /// Reads an escape sequence such as `\n` or `\u{e9}`, reporting it if it's invalid
+    fn escape(&mut self) -> Option<char> {
+        let start = self.index;
+        self.next(); // skip \
+        let ch = match self.next() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '\'' => Some('\''),
+            'x' => {
+                let digits = self.hex_digits(2);
+                u8::from_str_radix(digits, 16).ok().filter(|n| n.is_ascii() && digits.len() == 2).map(char::from)
+            }
+            'u' if self.peek() == '{' => {
+                self.next();
+                let digits = self.hex_digits(6);
+                let ch = u32::from_str_radix(digits, 16).ok().and_then(char::from_u32);
+                if self.peek() == '}' {
+                    self.next();
+                    ch
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if ch.is_none() {
+            self.diagnostics.emit(
+                Error::new(
+                    SyntaxError,
+                    format!("Invalid escape sequence `{}`", &self.code[start..self.index]).as_str(),
+                    self.file,
+                    start,
+                    self.index - start,
+                )
+                .code("E0017")
+                .help("the escapes are \\n, \\t, \\r, \\0, \\\\, \\\", \\', \\x00 to \\x7F and \\u{0} to \\u{10FFFF}"),
+            );
+        }
+        ch
+    }
+
+    /// Reads up to `max` hex digits
+    fn hex_digits(&mut self, max: usize) -> &'l str {
+        let start = self.index;
+        while self.peek().is_ascii_hexdigit() && self.index - start < max {
+            self.next();
+        }
+        &self.code[start..self.index]
+    }
+
+    fn special(&mut self) -> Option<Token> {
         match self.peek() {
             '(' | '[' => self.nesting += 1,
-            ')' | ']' => self.nesting -= 1,
-            ',' | '=' | ':' | '{' | '}' => (),
-            _ => unreachable!(),
+            // an unmatched closing bracket is left for the parser to report
+            ')' | ']' => self.nesting = self.nesting.saturating_sub(1),
+            // brackets can't hold blocks, so any still open were never closed
+            '{' | '}' => self.nesting = 0,
+            _ => (),
         };
-        match self.next() {
+        let token = match self.peek() {
             '(' => Token::LParen,
             ')' => Token::RParen,
             '[' => Token::LBracket,
@@ -158,80 +415,155 @@ impl<'l> Lexer<'l> {
             ',' => Token::Comma,
             '=' => Token::Equals,
             ':' => Token::Colon,
-            _ => unreachable!(),
-        }
+            _ => {
+                self.unexpected_char();
+                return None;
+            }
+        };
+        self.next();
+        Some(token)
+    }
+
+    /// Reports and skips a character which can't start any token
+    fn unexpected_char(&mut self) {
+        let start = self.index;
+        let ch = self.next();
+        self.diagnostics.syntax_error(
+            "E0011",
+            format!("Unexpected character {:?}", ch).as_str(),
+            self.file,
+            start,
+            self.index - start,
+        );
     }
 
+    /// Skips a comment up to the end of the line, leaving the line break to end the statement
     fn comment(&mut self) {
         self.next(); // throwaway initial #
-        while self.peek() != '\n' && self.peek() != '\0' {
+        while self.peek() != '\n' && self.index < self.code.len() {
             self.next();
         }
-        self.next();
+    }
+
+    /// Skips a `#[ ... ]#` comment, which can hold other block comments
+    fn block_comment(&mut self) {
+        let start = self.index;
+        let mut depth = 0;
+        loop {
+            let rest = &self.code[self.index..];
+            if rest.starts_with("#[") {
+                depth += 1;
+                self.index += 2;
+            } else if rest.starts_with("]#") {
+                depth -= 1;
+                self.index += 2;
+                if depth == 0 {
+                    return;
+                }
+            } else if rest.is_empty() {
+                let terminator = "]#".repeat(depth);
+                self.diagnostics.emit(
+                    Error::new(SyntaxError, "Encountered end of file while parsing block comment", self.file, start, self.index - start)
+                        .code("E0021")
+                        .suggest(format!("add `{}` to end the comment", terminator).as_str(), self.index, 0, &terminator),
+                );
+                return;
+            } else {
+                self.next();
+            }
+        }
     }
 
     fn doc_comment(&mut self) -> Token {
         self.next(); // throwaway initial #
         self.next(); // throwaway initial :
         let mut doc_comment = String::new();
-        while self.peek() != '\n' && self.peek() != '\0' {
+        while self.peek() != '\n' && self.index < self.code.len() {
             doc_comment.push(self.next());
         }
-        self.next();
         Token::DocComment(doc_comment)
     }
 
     pub fn go(&mut self) -> Option<Vec<Span<Token>>> {
+        match self.go_partial() {
+            (tokens, None) => Some(tokens),
+            (_, Some(_)) => None,
+        }
+    }
+
+    /// Lexes as far as it can, which is the whole file unless a string runs off the end of it
+    ///
+    /// Along with the tokens, gives where the unterminated string starts if there is one.
+    pub fn go_partial(&mut self) -> (Vec<Span<Token>>, Option<usize>) {
         let mut tokens = vec![];
         loop {
+            let start = self.index;
             match self.peek() {
+                'r' if self.is_raw_string() => {
+                    let string = match self.raw_string() {
+                        Some(string) => string,
+                        None => return (tokens, Some(start)),
+                    };
+                    tokens.push(self.spanned(string, start));
+                }
                 ch if is_ident_start(ch) => {
                     let id = self.ident_str();
                     tokens.push(
-                        self.spanned(str_to_keyword(&id).unwrap_or_else(|| str_to_ident(&id))),
+                        self.spanned(str_to_keyword(id).unwrap_or_else(|| str_to_ident(id)), start),
                     );
                 }
                 '.' => {
-                    if is_number(self.code[self.index + 1], false) {
-                        let number = self.number();
-                        tokens.push(self.spanned(number));
+                    if self.peek_next().is_ascii_digit() {
+                        let number = self.number(negated(&tokens));
+                        tokens.push(self.spanned(number, start));
                     } else {
-                        tokens.push(self.spanned(Token::Op(".".to_owned())));
                         self.next();
+                        tokens.push(self.spanned(Token::Op(".".to_owned()), start));
                     }
                 }
-                ch if is_number(ch, false) => {
-                    let number = self.number();
-                    tokens.push(self.spanned(number));
+                ch if ch.is_ascii_digit() => {
+                    let number = self.number(negated(&tokens));
+                    tokens.push(self.spanned(number, start));
                 }
                 '=' => {
-                    if self.code[self.index + 1] == '=' {
+                    if self.peek_next() == '=' {
                         let operator = self.operator();
-                        tokens.push(self.spanned(operator));
+                        tokens.push(self.spanned(operator, start));
                     } else {
-                        let special = self.special();
-                        tokens.push(self.spanned(special));
+                        if let Some(special) = self.special() {
+                            tokens.push(self.spanned(special, start));
+                        }
                     }
                 }
                 '#' => {
-                    if self.code[self.index + 1] == ':' {
+                    if self.peek_next() == ':' {
                         let doc_comment = self.doc_comment();
-                        tokens.push(self.spanned(doc_comment));
+                        tokens.push(self.spanned(doc_comment, start));
+                    } else if self.peek_next() == '[' {
+                        self.block_comment();
                     } else {
                         self.comment();
                     }
                 }
                 ch if is_special(ch) => {
-                    let special = self.special();
-                    tokens.push(self.spanned(special));
+                    if let Some(special) = self.special() {
+                        tokens.push(self.spanned(special, start));
+                    }
                 }
                 '"' => {
-                    let string = self.string()?;
-                    tokens.push(self.spanned(string));
+                    let string = match self.string() {
+                        Some(string) => string,
+                        None => return (tokens, Some(start)),
+                    };
+                    tokens.push(self.spanned(string, start));
+                }
+                '\'' => {
+                    let ch = self.char_literal();
+                    tokens.push(self.spanned(ch, start));
                 }
                 ch if is_op(ch) => {
                     let operator = self.operator();
-                    tokens.push(self.spanned(operator));
+                    tokens.push(self.spanned(operator, start));
                 }
                 ch if ch == '\n' => {
                     // token::proc doesn't matter, just needs to be
@@ -243,52 +575,54 @@ impl<'l> Lexer<'l> {
                             .last()
                             .unwrap_or(&Span {
                                 contents: Token::Proc,
+                                file: self.file,
                                 pos: 0,
                                 len: 0,
                             })
                             .contents
                         {
-                            Token::Op(_) | Token::Comma => self.next(),
-                            _ if self.nesting != 0 => self.next(),
+                            Token::Op(_) | Token::Comma => {
+                                self.next();
+                            }
+                            _ if self.nesting != 0 => {
+                                self.next();
+                            }
                             _ => {
-                                tokens.push(self.spanned(Token::Newline));
-                                self.next()
+                                self.next();
+                                tokens.push(self.spanned(Token::Newline, start));
                             }
-                        };
+                        }
                     }
                 }
                 ch if ch.is_ascii_whitespace() => {
                     self.next();
                 }
-                '\0' => break,
-                _ => unreachable!(),
+                '\0' if self.index >= self.code.len() => break,
+                _ => self.unexpected_char(),
             }
         }
-        Some(tokens)
+        (tokens, None)
     }
 
-    fn spanned(&mut self, token: Token) -> Span<Token> {
+    /// Spans a token from `start` up to the current position
+    fn spanned(&self, token: Token, start: usize) -> Span<Token> {
         Span {
-            contents: token.clone(),
-            pos: self.index,
-            len: token_len(&token),
+            contents: token,
+            file: self.file,
+            pos: start,
+            len: self.index - start,
         }
     }
 }
 
 #[inline]
 fn is_ident(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || ch == '_'
+    UnicodeXID::is_xid_continue(ch)
 }
 
 #[inline]
 fn is_ident_start(ch: char) -> bool {
-    ch.is_ascii_alphabetic() || ch == '_'
-}
-
-#[inline]
-fn is_number(ch: char, decimal_passed: bool) -> bool {
-    ch.is_ascii_digit() || (ch == '.' && !decimal_passed)
+    UnicodeXID::is_xid_start(ch) || ch == '_'
 }
 
 #[inline]
@@ -296,9 +630,10 @@ fn is_special(ch: char) -> bool {
     SPECIAL_CHARS.contains(&ch)
 }
 
+/// Whether `ch` can be part of an operator, though not every run of them is one
 #[inline]
 fn is_op(ch: char) -> bool {
-    ch.is_ascii_punctuation()
+    ch.is_ascii_punctuation() && !is_special(ch) && !"\"'#._".contains(ch)
 }
 
 fn str_to_keyword(s: &str) -> Option<Token> {
@@ -319,47 +654,65 @@ fn str_to_keyword(s: &str) -> Option<Token> {
     })
 }
 
+/// The type given by a number literal's suffix
+fn suffix_type(s: &str) -> Option<Type> {
+    Some(match s {
+        "i8" => Type::I8,
+        "i16" => Type::I16,
+        "i32" => Type::I32,
+        "i64" => Type::I64,
+        "i128" => Type::I128,
+        "n8" => Type::N8,
+        "n16" => Type::N16,
+        "n32" => Type::N32,
+        "n64" => Type::N64,
+        "n128" => Type::N128,
+        "f32" => Type::F32,
+        "f64" => Type::F64,
+        "f128" => Type::F128,
+        _ => return None,
+    })
+}
+
+/// The largest literal of an integer type, which for signed types is one more
+/// after a `-`, so that the minimum can be written
+fn max_value(typ: &Type, negated: bool) -> u128 {
+    let signed = |bits: u32| if negated { 1 << (bits - 1) } else { (1 << (bits - 1)) - 1 };
+    match typ {
+        Type::I8 => signed(8),
+        Type::I16 => signed(16),
+        Type::I32 => signed(32),
+        Type::I64 => signed(64),
+        Type::I128 => signed(128),
+        Type::N8 => u8::MAX.into(),
+        Type::N16 => u16::MAX.into(),
+        Type::N32 => u32::MAX.into(),
+        Type::N64 => u64::MAX.into(),
+        _ => u128::MAX,
+    }
+}
+
+/// Whether the last of `tokens` is a unary `-`, rather than a subtraction
+fn negated(tokens: &[Span<Token>]) -> bool {
+    let is_minus = |t: &Span<Token>| matches!(&t.contents, Token::Op(op) if op == "-" || op == "-~");
+    match tokens {
+        [.., before, minus] if is_minus(minus) => !matches!(
+            before.contents,
+            Token::IntLiteral(..)
+                | Token::FloatLiteral(..)
+                | Token::StrLiteral(_)
+                | Token::CharLiteral(_)
+                | Token::Ident(_)
+                | Token::RParen
+                | Token::RBracket
+        ),
+        [minus] => is_minus(minus),
+        _ => false,
+    }
+}
+
 #[inline]
 fn str_to_ident(s: &str) -> Token {
     Token::Ident(s.to_owned())
 }
 
-fn token_len(t: &Token) -> usize {
-    match t {
-        Token::IntLiteral(s) => s.len(),
-        Token::FloatLiteral(s) => s.len(),
-        Token::StrLiteral(s) => s.len(),
-
-        Token::Ident(s) => s.len(),
-        Token::Op(s) => s.len(),
-
-        Token::DocComment(s) => s.len() + 2,
-
-        Token::Proc => 4,
-        Token::If => 2,
-        Token::Else => 4,
-        Token::Elif => 4,
-        Token::While => 5,
-        Token::Loop => 4,
-        Token::Var => 3,
-        Token::Const => 5,
-        Token::Return => 6,
-        Token::Use => 3,
-        Token::Break => 5,
-        Token::Continue => 8,
-
-        Token::LParen
-        | Token::RParen
-        | Token::LBracket
-        | Token::RBracket
-        | Token::LBrace
-        | Token::RBrace
-        | Token::Comma
-        | Token::Equals
-        | Token::Colon => 1,
-
-        // newline
-        Token::Newline => 1,
-        Token::EOF => unreachable!(),
-    }
-}
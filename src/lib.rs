@@ -0,0 +1,46 @@
+//! The Elgin compiler
+
+pub mod errors;
+pub mod source_map;
+pub mod codes;
+pub mod render;
+pub mod types;
+
+pub mod lexer;
+pub mod parser;
+pub mod astgen;
+pub mod cst;
+pub mod ir;
+pub mod analysis;
+pub mod lints;
+pub mod llvm;
+
+pub mod doc;
+
+use errors::Diagnostics;
+use ir::IRProc;
+use source_map::{FileId, SourceMap};
+
+/// Runs the front end (lexer, parser, IR builder, analysis and lints) over `file`
+///
+/// This must never panic, whatever the source is: problems are logged to
+/// `diagnostics` instead. `fuzz/fuzz_targets/front_end.rs` checks this.
+pub fn check(sources: &SourceMap, file: FileId, diagnostics: &Diagnostics) -> Option<Vec<IRProc>> {
+    let tokens = lexer::Lexer::new(sources.get(file).source(), file, diagnostics).go()?;
+    let mut parser = parser::Parser::new(&tokens, file, diagnostics);
+    let ast = checked(parser.go(), diagnostics)?;
+    let mut irbuilder = ir::IRBuilder::new(&ast, parser.available_type_var, diagnostics);
+    checked(irbuilder.go(), diagnostics)?;
+    checked(irbuilder.analyze(), diagnostics)?;
+    irbuilder.lint();
+    checked(Some(irbuilder.procs), diagnostics)
+}
+
+/// Passes a stage's result through, failing if the stage logged any errors
+pub fn checked<T>(result: Option<T>, diagnostics: &Diagnostics) -> Option<T> {
+    if diagnostics.has_errors() {
+        None
+    } else {
+        result
+    }
+}
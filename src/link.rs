@@ -0,0 +1,24 @@
+//! Linking object files into executables with the system C compiler
+
+use std::env;
+use std::process::Command;
+
+/// Links `objects` into an executable at `output`, using `$CC` if it is set and `cc` otherwise
+pub fn link(objects: &[&str], output: &str) -> Result<(), String> {
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+    let result = Command::new(&cc)
+        .args(objects)
+        .arg("-o")
+        .arg(output)
+        .output();
+    match result {
+        Ok(out) if out.status.success() => Ok(()),
+        Ok(out) => Err(format!(
+            "{} exited with {}:\n{}",
+            cc,
+            out.status,
+            String::from_utf8_lossy(&out.stderr).trim_end(),
+        )),
+        Err(e) => Err(format!("Couldn't run {}: {}", cc, e)),
+    }
+}
@@ -0,0 +1,78 @@
+//! Lints: checks for code which is allowed but probably wrong
+//! Each lint warns by default and can be turned off or made fatal with -A/-D
+
+use crate::errors::{Error, ErrorType};
+use crate::ir::{IRBuilder, InstructionType};
+
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Debug)]
+pub struct Lint {
+    pub name: &'static str,
+    pub default: LintLevel,
+    pub desc: &'static str,
+}
+
+pub static UNUSED_VARIABLES: Lint = Lint {
+    name: "unused_variables",
+    default: LintLevel::Warn,
+    desc: "variables which are declared but never read",
+};
+
+pub static UNUSED_DOC_COMMENTS: Lint = Lint {
+    name: "unused_doc_comments",
+    default: LintLevel::Warn,
+    desc: "doc comments which aren't followed by a proc or const",
+};
+
+pub static LINTS: &[&Lint] = &[&UNUSED_VARIABLES, &UNUSED_DOC_COMMENTS];
+
+/// Looks up a lint by name, allowing `-` in place of `_`
+pub fn find(name: &str) -> Option<&'static Lint> {
+    let name = name.replace('-', "_");
+    LINTS.iter().copied().find(|lint| lint.name == name)
+}
+
+impl<'i> IRBuilder<'i> {
+    /// Runs every lint over the analyzed procs
+    pub fn lint(&self) {
+        self.unused_variables();
+    }
+
+    fn unused_variables(&self) {
+        for proc in &self.procs {
+            let read: HashSet<_> = proc
+                .body
+                .iter()
+                .filter_map(|ins| match &ins.contents.ins {
+                    InstructionType::Load(var) => Some(var),
+                    _ => None,
+                })
+                .collect();
+            for ins in &proc.body {
+                if let InstructionType::Allocate(var) = &ins.contents.ins {
+                    if !read.contains(var) && !var.starts_with('_') {
+                        self.diagnostics.lint(
+                            &UNUSED_VARIABLES,
+                            Error::new(
+                                ErrorType::Lint,
+                                format!("The variable {} is never read", var).as_str(),
+                                ins.file,
+                                ins.pos,
+                                ins.len,
+                            )
+                            .help(format!("if this is intentional, name it _{} instead", var).as_str()),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,22 +2,79 @@
 
 extern crate llvm_sys as llvm;
 
+use llvm::analysis::*;
+use llvm::bit_writer::*;
 use llvm::core::*;
+use llvm::debuginfo::*;
+use llvm::execution_engine::*;
 use llvm::prelude::*;
+use llvm::support::LLVMLoadLibraryPermanently;
+use llvm::target::*;
+use llvm::target_machine::*;
+use llvm::transforms::pass_manager_builder::*;
 
 use std::collections::HashMap;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::{mem, ptr, slice};
 
 use crate::ir::{CompareType, IRProc, Instruction, InstructionType};
 use crate::types::Type;
-use crate::errors::Span;
+use crate::errors::{Diagnostics, Span};
+use crate::source_map::{FileId, SourceFile};
+
+#[derive(Debug, Clone, Copy)]
+pub enum FileType {
+    Assembly,
+    Object,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    Os,
+}
+
+impl OptLevel {
+    /// The command line flag which picks the level
+    pub fn as_flag(self) -> &'static str {
+        match self {
+            OptLevel::O0 => "-O0",
+            OptLevel::O1 => "-O1",
+            OptLevel::O2 => "-O2",
+            OptLevel::O3 => "-O3",
+            OptLevel::Os => "-Os",
+        }
+    }
+}
+
+/// DWARF metadata, built alongside the code when debug info is enabled
+struct DebugInfo<'g> {
+    builder: LLVMDIBuilderRef,
+    file: LLVMMetadataRef,
+    source: &'g SourceFile,
+    scope: LLVMMetadataRef,
+}
+
+// DWARF base type encodings
+const DW_ATE_BOOLEAN: LLVMDWARFTypeEncoding = 0x02;
+const DW_ATE_FLOAT: LLVMDWARFTypeEncoding = 0x04;
+const DW_ATE_SIGNED: LLVMDWARFTypeEncoding = 0x05;
+const DW_ATE_UNSIGNED: LLVMDWARFTypeEncoding = 0x08;
 
 pub struct Generator<'g> {
     procs: &'g [IRProc],
+    diagnostics: &'g Diagnostics,
 
     context: *mut llvm::LLVMContext,
     builder: *mut llvm::LLVMBuilder,
     module: *mut llvm::LLVMModule,
+    target_machine: LLVMTargetMachineRef,
+    opt_level: OptLevel,
+    debug: Option<DebugInfo<'g>>,
 
     strings: Vec<CString>,
 
@@ -25,17 +82,18 @@ pub struct Generator<'g> {
     lookup: HashMap<String, LLVMValueRef>,
     labels: HashMap<usize, LLVMBasicBlockRef>,
     llvm_procs: HashMap<String, LLVMValueRef>,
+    /// Where each generated instruction and label came from, to blame verifier failures on
+    spans: HashMap<LLVMValueRef, (FileId, usize, usize)>,
 
     current_proc: LLVMValueRef,
 }
 
 impl<'g> Generator<'g> {
-    pub fn new(procs: &'g [IRProc], module_name: &str, file_name: &str) -> Self {
+    pub fn new(procs: &'g [IRProc], diagnostics: &'g Diagnostics, module_name: &str, file_name: &str) -> Self {
         let context = unsafe { LLVMContextCreate() };
         let builder = unsafe { LLVMCreateBuilderInContext(context) };
-        let module = unsafe {
-            LLVMModuleCreateWithNameInContext(module_name.as_bytes().as_ptr() as *const _, context)
-        };
+        let module_name = CString::new(module_name).unwrap();
+        let module = unsafe { LLVMModuleCreateWithNameInContext(module_name.as_ptr(), context) };
         unsafe {
             LLVMSetSourceFileName(
                 module,
@@ -46,10 +104,14 @@ impl<'g> Generator<'g> {
 
         Generator {
             procs,
+            diagnostics,
 
             context,
             builder,
             module,
+            target_machine: ptr::null_mut(),
+            opt_level: OptLevel::O0,
+            debug: None,
 
             strings: vec![],
 
@@ -57,12 +119,13 @@ impl<'g> Generator<'g> {
             lookup: HashMap::new(),
             labels: HashMap::new(),
             llvm_procs: HashMap::new(),
+            spans: HashMap::new(),
 
             current_proc: 0 as LLVMValueRef,
         }
     }
 
-    pub fn go(&mut self) {
+    pub fn go(&mut self) -> Option<()> {
         // Create declarations first
         for proc in self.procs {
             unsafe {
@@ -102,13 +165,112 @@ impl<'g> Generator<'g> {
                     self.cstr("entry"),
                 );
                 LLVMPositionBuilderAtEnd(self.builder, bb);
+                self.debug_proc(proc);
                 for (i, name) in proc.args.iter().enumerate() {
                     self.stack.push(LLVMGetParam(self.current_proc, i as u32));
                     self.allocate(name.clone(), proc.arg_types[i].clone());
+                    self.debug_variable(name, &proc.arg_types[i], Some(i as u32 + 1), proc.pos);
                 }
             }
             for ins in &proc.body {
-                self.ins(&ins.clone());
+                self.ins_with_span(ins);
+            }
+        }
+        if let Some(debug) = &self.debug {
+            unsafe { LLVMDIBuilderFinalize(debug.builder) };
+        }
+        self.verify()
+    }
+
+    /// Checks the module with the LLVM verifier, reporting anything it finds as an internal
+    /// compiler error against the instruction it was generated from
+    fn verify(&mut self) -> Option<()> {
+        unsafe {
+            let mut error_msg = ptr::null_mut();
+            let failed = LLVMVerifyModule(
+                self.module,
+                LLVMVerifierFailureAction::LLVMReturnStatusAction,
+                &mut error_msg,
+            ) == 1;
+            let msg = take_message(error_msg);
+            if !failed {
+                return Some(());
+            }
+
+            let mut blamed = false;
+            for proc in self.procs {
+                let function = self.llvm_procs[&proc.name];
+                if LLVMVerifyFunction(function, LLVMVerifierFailureAction::LLVMReturnStatusAction) == 1 {
+                    let (file, pos, len) = self
+                        .culprit(function, &msg)
+                        .unwrap_or((proc.file, proc.pos, proc.len));
+                    self.diagnostics.internal_error(
+                        format!("LLVM rejected the code generated for proc {}: {}", proc.name, msg.trim()).as_str(),
+                        file,
+                        pos,
+                        len,
+                    );
+                    blamed = true;
+                }
+            }
+            if !blamed {
+                self.diagnostics.internal_error(
+                    format!("LLVM rejected the generated module: {}", msg.trim()).as_str(),
+                    FileId::default(),
+                    0,
+                    0,
+                );
+            }
+            None
+        }
+    }
+
+    /// The span of the first instruction or label in `function` which the verifier's message
+    /// quotes, as it prints the values it objects to at the ends of lines
+    unsafe fn culprit(&self, function: LLVMValueRef, msg: &str) -> Option<(FileId, usize, usize)> {
+        let quoted: Vec<&str> = msg.lines().map(str::trim).collect();
+        let mut bb = LLVMGetFirstBasicBlock(function);
+        while !bb.is_null() {
+            let name = CStr::from_ptr(LLVMGetBasicBlockName(bb)).to_string_lossy();
+            if quoted.contains(&format!("label %{}", name).as_str()) {
+                if let Some(&span) = self.spans.get(&LLVMBasicBlockAsValue(bb)) {
+                    return Some(span);
+                }
+            }
+            let mut value = LLVMGetFirstInstruction(bb);
+            while !value.is_null() {
+                if let Some(&span) = self.spans.get(&value) {
+                    let text = take_message(LLVMPrintValueToString(value));
+                    if quoted.iter().any(|line| line.ends_with(text.trim())) {
+                        return Some(span);
+                    }
+                }
+                value = LLVMGetNextInstruction(value);
+            }
+            bb = LLVMGetNextBasicBlock(bb);
+        }
+        None
+    }
+
+    /// Generates `ins`, remembering its span for everything it added to the function
+    fn ins_with_span(&mut self, ins: &Span<Instruction>) {
+        let span = (ins.file, ins.pos, ins.len);
+        unsafe {
+            let before = LLVMGetInsertBlock(self.builder);
+            let last = LLVMGetLastInstruction(before);
+            self.ins(ins);
+            let block = LLVMGetInsertBlock(self.builder);
+            if let InstructionType::Label(_) = ins.contents.ins {
+                self.spans.insert(LLVMBasicBlockAsValue(block), span);
+            }
+            let mut value = if block == before && !last.is_null() {
+                LLVMGetNextInstruction(last)
+            } else {
+                LLVMGetFirstInstruction(block)
+            };
+            while !value.is_null() {
+                self.spans.insert(value, span);
+                value = LLVMGetNextInstruction(value);
             }
         }
     }
@@ -116,12 +278,24 @@ impl<'g> Generator<'g> {
     fn ins(&mut self, ins: &Span<Instruction>) {
         use crate::ir::InstructionType::*;
         let typ = ins.contents.typ.clone();
+        self.debug_location(ins.pos);
+        unsafe {
+            // anything between a terminator (like the jump for a break) and the next label is
+            // unreachable, and LLVM doesn't allow it in the same basic block
+            let terminated = !LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(self.builder)).is_null();
+            if terminated && !matches!(ins.contents.ins, Label(_)) {
+                return;
+            }
+        }
         match ins.clone().contents.ins {
-            Push(s) => self.push(s, typ),
+            Push(s) => self.push(s, typ, ins),
             Load(s) => self.load(s, typ),
             Store(s) => self.store(s, typ),
             StoreIndexed(s) => self.store_indexed(s, typ),
-            Allocate(s) => self.allocate(s, typ),
+            Allocate(s) => {
+                self.allocate(s.clone(), typ.clone());
+                self.debug_variable(&s, &typ, None, ins.pos);
+            }
 
             Index => self.index(typ),
 
@@ -144,22 +318,21 @@ impl<'g> Generator<'g> {
         }
     }
 
-    fn push(&mut self, s: String, typ: Type) {
+    fn push(&mut self, s: String, typ: Type, ins: &Span<Instruction>) {
         unsafe {
             let obj = match typ {
                 Type::I8
                 | Type::I16
                 | Type::I32
                 | Type::I64
-                | Type::I128 => {
-                    LLVMConstInt(self.llvm_type(&typ), s.parse().unwrap(), 0)
-                }
-                Type::N8
+                | Type::I128
+                | Type::N8
                 | Type::N16
                 | Type::N32
                 | Type::N64
                 | Type::N128 => {
-                    LLVMConstInt(self.llvm_type(&typ), s.parse().unwrap(), 0)
+                    // literals can be up to 128 bits, too big for LLVMConstInt
+                    LLVMConstIntOfStringAndSize(self.llvm_type(&typ), s.as_ptr() as *const _, s.len() as u32, 10)
                 }
                 Type::F32
                 | Type::F64
@@ -169,9 +342,7 @@ impl<'g> Generator<'g> {
                 Type::Undefined => {
                     LLVMGetUndef(self.llvm_type(&Type::I8))
                 }
-                Type::StrLiteral => {
-                    LLVMBuildGlobalStringPtr(self.builder, self.cstr(&s), self.cstr("tmpstr"))
-                }
+                Type::StrLiteral => self.global_string(&s),
                 Type::Bool => {
                     let boolean = match s.as_str() {
                         "true" => 1,
@@ -180,12 +351,37 @@ impl<'g> Generator<'g> {
                     };
                     LLVMConstInt(LLVMInt1TypeInContext(self.context), boolean, 0)
                 }
-                t => todo!("{:?}", t),
+                t => {
+                    // analysis should have reported the type before getting here
+                    self.diagnostics.internal_error(
+                        format!("Can't generate a value of type {:?}", t).as_str(),
+                        ins.file,
+                        ins.pos,
+                        ins.len,
+                    );
+                    LLVMGetUndef(LLVMInt8TypeInContext(self.context))
+                }
             };
             self.stack.push(obj);
         }
     }
 
+    /// A pointer to a private copy of `s`, which unlike a C string may hold `\0`s
+    fn global_string(&mut self, s: &str) -> LLVMValueRef {
+        unsafe {
+            let contents = LLVMConstStringInContext(self.context, s.as_ptr() as *const _, s.len() as u32, 0);
+            let global = LLVMAddGlobal(self.module, LLVMTypeOf(contents), self.cstr("tmpstr"));
+            LLVMSetInitializer(global, contents);
+            LLVMSetGlobalConstant(global, 1);
+            LLVMSetLinkage(global, llvm::LLVMLinkage::LLVMPrivateLinkage);
+            LLVMSetUnnamedAddress(global, llvm::LLVMUnnamedAddr::LLVMGlobalUnnamedAddr);
+            LLVMSetAlignment(global, 1);
+            let zero = LLVMConstInt(LLVMInt32TypeInContext(self.context), 0, 0);
+            let mut indices = [zero, zero];
+            LLVMConstInBoundsGEP(global, indices.as_mut_ptr(), 2)
+        }
+    }
+
     fn load(&mut self, s: String, typ: Type) {
         let var = self.lookup.get(&s).unwrap();
         unsafe {
@@ -256,8 +452,14 @@ impl<'g> Generator<'g> {
             for _ in 0..arg_count {
                 args.insert(0, self.stack.pop().unwrap());
             }
-            let call = LLVMBuildCall(self.builder, proc, args.as_mut_ptr(), args.len() as u32, self.cstr("tmpcall"));
-            // FIXME calls to functions that return void need to not be named HOW TO DO THIS?
+            // calls to procs that return nothing produce no value, so they can't be named
+            let ret_type = LLVMGetReturnType(LLVMGetElementType(LLVMTypeOf(proc)));
+            let name = if LLVMGetTypeKind(ret_type) == llvm::LLVMTypeKind::LLVMVoidTypeKind {
+                ""
+            } else {
+                "tmpcall"
+            };
+            let call = LLVMBuildCall(self.builder, proc, args.as_mut_ptr(), args.len() as u32, self.cstr(name));
             self.stack.push(call);
         }
     }
@@ -640,15 +842,398 @@ impl<'g> Generator<'g> {
         }
     }
 
-    pub fn dump_to_file(&mut self, file: &str) -> bool {
+    pub fn ir_string(&self) -> String {
+        unsafe { take_message(LLVMPrintModuleToString(self.module)) }
+    }
+
+    pub fn bitcode(&self) -> Vec<u8> {
+        unsafe { buffer_contents(LLVMWriteBitcodeToMemoryBuffer(self.module)) }
+    }
+
+    /// Starts emitting DWARF debug info for `source`, which must happen before go
+    pub fn enable_debug_info(&mut self, source: &'g SourceFile) {
+        let path = Path::new(source.name());
+        let name = path.file_name().map_or(source.name().into(), |n| n.to_string_lossy());
+        let dir = path.parent().map_or("".into(), |d| d.to_string_lossy());
+        let producer = "elgin";
+        unsafe {
+            let builder = LLVMCreateDIBuilder(self.module);
+            let file = LLVMDIBuilderCreateFile(
+                builder,
+                name.as_ptr() as *const _,
+                name.len(),
+                dir.as_ptr() as *const _,
+                dir.len(),
+            );
+            LLVMDIBuilderCreateCompileUnit(
+                builder,
+                LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageC,
+                file,
+                producer.as_ptr() as *const _,
+                producer.len(),
+                (self.opt_level != OptLevel::O0) as i32,
+                ptr::null(),
+                0,
+                0,
+                ptr::null(),
+                0,
+                LLVMDWARFEmissionKind::LLVMDWARFEmissionKindFull,
+                0,
+                0,
+                0,
+            );
+
+            let i32_type = LLVMInt32TypeInContext(self.context);
+            for (key, value) in &[("Debug Info Version", LLVMDebugMetadataVersion()), ("Dwarf Version", 4)] {
+                LLVMAddModuleFlag(
+                    self.module,
+                    llvm::LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning,
+                    key.as_ptr() as *const _,
+                    key.len(),
+                    LLVMValueAsMetadata(LLVMConstInt(i32_type, *value as u64, 0)),
+                );
+            }
+
+            self.debug = Some(DebugInfo {
+                builder,
+                file,
+                source,
+                scope: ptr::null_mut(),
+            });
+        }
+    }
+
+    /// Attaches a DISubprogram to the proc being generated
+    fn debug_proc(&mut self, proc: &IRProc) {
+        if self.debug.is_none() {
+            return;
+        }
+        let mut param_types: Vec<_> = std::iter::once(&proc.ret_type)
+            .chain(proc.arg_types.iter())
+            .map(|t| self.debug_type(t))
+            .collect();
+        let debug = self.debug.as_mut().unwrap();
+        let (line, _) = debug.source.line_col(proc.pos);
+        unsafe {
+            let proc_type = LLVMDIBuilderCreateSubroutineType(
+                debug.builder,
+                debug.file,
+                param_types.as_mut_ptr(),
+                param_types.len() as u32,
+                LLVMDIFlagZero,
+            );
+            debug.scope = LLVMDIBuilderCreateFunction(
+                debug.builder,
+                debug.file,
+                proc.name.as_ptr() as *const _,
+                proc.name.len(),
+                proc.name.as_ptr() as *const _,
+                proc.name.len(),
+                debug.file,
+                line as u32,
+                proc_type,
+                0,
+                1,
+                line as u32,
+                LLVMDIFlagPrototyped,
+                (self.opt_level != OptLevel::O0) as i32,
+            );
+            LLVMSetSubprogram(self.current_proc, debug.scope);
+        }
+        self.debug_location(proc.pos);
+    }
+
+    fn debug_location(&mut self, pos: usize) {
+        if let Some(debug) = &self.debug {
+            let (line, col) = debug.source.line_col(pos);
+            unsafe {
+                let loc = LLVMDIBuilderCreateDebugLocation(
+                    self.context,
+                    line as u32,
+                    col as u32,
+                    debug.scope,
+                    ptr::null_mut(),
+                );
+                LLVMSetCurrentDebugLocation2(self.builder, loc);
+            }
+        }
+    }
+
+    /// Describes the variable `name` which was just allocated, as a parameter if `arg_no` is given
+    fn debug_variable(&mut self, name: &str, typ: &Type, arg_no: Option<u32>, pos: usize) {
+        if self.debug.is_none() {
+            return;
+        }
+        let di_type = self.debug_type(typ);
+        if di_type.is_null() {
+            return;
+        }
+        let debug = self.debug.as_ref().unwrap();
+        let (line, col) = debug.source.line_col(pos);
+        unsafe {
+            let var = match arg_no {
+                Some(arg_no) => LLVMDIBuilderCreateParameterVariable(
+                    debug.builder,
+                    debug.scope,
+                    name.as_ptr() as *const _,
+                    name.len(),
+                    arg_no,
+                    debug.file,
+                    line as u32,
+                    di_type,
+                    1,
+                    LLVMDIFlagZero,
+                ),
+                None => LLVMDIBuilderCreateAutoVariable(
+                    debug.builder,
+                    debug.scope,
+                    name.as_ptr() as *const _,
+                    name.len(),
+                    debug.file,
+                    line as u32,
+                    di_type,
+                    1,
+                    LLVMDIFlagZero,
+                    0,
+                ),
+            };
+            let loc = LLVMDIBuilderCreateDebugLocation(
+                self.context,
+                line as u32,
+                col as u32,
+                debug.scope,
+                ptr::null_mut(),
+            );
+            LLVMDIBuilderInsertDeclareAtEnd(
+                debug.builder,
+                self.lookup[name],
+                var,
+                LLVMDIBuilderCreateExpression(debug.builder, ptr::null_mut(), 0),
+                loc,
+                LLVMGetInsertBlock(self.builder),
+            );
+        }
+    }
+
+    /// The DWARF type for `t`, or null for types without a runtime representation
+    fn debug_type(&mut self, t: &Type) -> LLVMMetadataRef {
+        let builder = match &self.debug {
+            Some(debug) => debug.builder,
+            None => return ptr::null_mut(),
+        };
+        let (name, encoding) = match t {
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::I128 => (format!("{:?}", t), DW_ATE_SIGNED),
+            Type::N8 | Type::N16 | Type::N32 | Type::N64 | Type::N128 => (format!("{:?}", t), DW_ATE_UNSIGNED),
+            Type::F32 | Type::F64 | Type::F128 => (format!("{:?}", t), DW_ATE_FLOAT),
+            Type::Bool => ("bool".to_owned(), DW_ATE_BOOLEAN),
+            Type::Ptr(content_type) => {
+                let content_type = self.debug_type(content_type);
+                let name = format!("{:?}", t);
+                unsafe {
+                    return LLVMDIBuilderCreatePointerType(
+                        builder,
+                        content_type,
+                        self.bit_size(t),
+                        0,
+                        0,
+                        name.as_ptr() as *const _,
+                        name.len(),
+                    );
+                }
+            }
+            Type::Array(size, content_type) => {
+                let content_type = self.debug_type(content_type);
+                unsafe {
+                    let mut subscripts = [LLVMDIBuilderGetOrCreateSubrange(builder, 0, *size as i64)];
+                    return LLVMDIBuilderCreateArrayType(
+                        builder,
+                        self.bit_size(t),
+                        0,
+                        content_type,
+                        subscripts.as_mut_ptr(),
+                        1,
+                    );
+                }
+            }
+            _ => return ptr::null_mut(),
+        };
+        unsafe {
+            LLVMDIBuilderCreateBasicType(
+                builder,
+                name.as_ptr() as *const _,
+                name.len(),
+                self.bit_size(t),
+                encoding,
+                LLVMDIFlagZero,
+            )
+        }
+    }
+
+    fn bit_size(&self, t: &Type) -> u64 {
+        unsafe { LLVMSizeOfTypeInBits(LLVMGetModuleDataLayout(self.module), self.llvm_type(t)) }
+    }
+
+    /// Runs the LLVM function and module pass pipelines for the optimization level given to
+    /// init_target
+    pub fn optimize(&mut self) {
+        let (opt, size, inline_threshold) = match self.opt_level {
+            OptLevel::O0 => return,
+            OptLevel::O1 => (1, 0, None),
+            OptLevel::O2 => (2, 0, Some(225)),
+            OptLevel::O3 => (3, 0, Some(275)),
+            OptLevel::Os => (2, 1, Some(75)),
+        };
+        unsafe {
+            let builder = LLVMPassManagerBuilderCreate();
+            LLVMPassManagerBuilderSetOptLevel(builder, opt);
+            LLVMPassManagerBuilderSetSizeLevel(builder, size);
+            if let Some(threshold) = inline_threshold {
+                LLVMPassManagerBuilderUseInlinerWithThreshold(builder, threshold);
+            }
+
+            let function_passes = LLVMCreateFunctionPassManagerForModule(self.module);
+            LLVMPassManagerBuilderPopulateFunctionPassManager(builder, function_passes);
+            LLVMInitializeFunctionPassManager(function_passes);
+            let mut function = LLVMGetFirstFunction(self.module);
+            while !function.is_null() {
+                LLVMRunFunctionPassManager(function_passes, function);
+                function = LLVMGetNextFunction(function);
+            }
+            LLVMFinalizeFunctionPassManager(function_passes);
+            LLVMDisposePassManager(function_passes);
+
+            let module_passes = LLVMCreatePassManager();
+            LLVMPassManagerBuilderPopulateModulePassManager(builder, module_passes);
+            LLVMRunPassManager(module_passes, self.module);
+            LLVMDisposePassManager(module_passes);
+
+            LLVMPassManagerBuilderDispose(builder);
+        }
+    }
+
+    /// Sets up a target machine for the host and records its triple and data layout on the module
+    pub fn init_target(&mut self, opt_level: OptLevel) -> Result<(), String> {
+        self.opt_level = opt_level;
+        unsafe {
+            if LLVM_InitializeNativeTarget() == 1 || LLVM_InitializeNativeAsmPrinter() == 1 {
+                return Err("The host target isn't supported by this build of LLVM".to_owned());
+            }
+            let triple = LLVMGetDefaultTargetTriple();
+            let mut target = ptr::null_mut();
+            let mut error_msg = ptr::null_mut();
+            if LLVMGetTargetFromTriple(triple, &mut target, &mut error_msg) == 1 {
+                LLVMDisposeMessage(triple);
+                return Err(take_message(error_msg));
+            }
+            let cpu = LLVMGetHostCPUName();
+            let features = LLVMGetHostCPUFeatures();
+            self.target_machine = LLVMCreateTargetMachine(
+                target,
+                triple,
+                cpu,
+                features,
+                match opt_level {
+                    OptLevel::O0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+                    OptLevel::O1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+                    OptLevel::O2 | OptLevel::Os => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+                    OptLevel::O3 => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+                },
+                LLVMRelocMode::LLVMRelocPIC,
+                LLVMCodeModel::LLVMCodeModelDefault,
+            );
+            LLVMSetTarget(self.module, triple);
+            let data_layout = LLVMCreateTargetDataLayout(self.target_machine);
+            LLVMSetModuleDataLayout(self.module, data_layout);
+            LLVMDisposeTargetData(data_layout);
+            LLVMDisposeMessage(triple);
+            LLVMDisposeMessage(cpu);
+            LLVMDisposeMessage(features);
+        }
+        Ok(())
+    }
+
+    pub fn native(&mut self, file_type: FileType) -> Result<Vec<u8>, String> {
+        if self.target_machine.is_null() {
+            self.init_target(self.opt_level)?;
+        }
         unsafe {
-            let mut error_msg: *mut i8 = ""
-                .as_bytes()
-                .iter()
-                .map(|b| *b as i8)
-                .collect::<Vec<_>>()
-                .as_mut_ptr();
-            LLVMPrintModuleToFile(self.module, self.cstr(file), &mut error_msg) == 1
+            let mut error_msg = ptr::null_mut();
+            let mut buffer = ptr::null_mut();
+            let failed = LLVMTargetMachineEmitToMemoryBuffer(
+                self.target_machine,
+                self.module,
+                match file_type {
+                    FileType::Assembly => LLVMCodeGenFileType::LLVMAssemblyFile,
+                    FileType::Object => LLVMCodeGenFileType::LLVMObjectFile,
+                },
+                &mut error_msg,
+                &mut buffer,
+            );
+            if failed == 1 {
+                Err(take_message(error_msg))
+            } else {
+                Ok(buffer_contents(buffer))
+            }
+        }
+    }
+
+    /// Compiles the module in memory with MCJIT and calls its main proc, returning what it returns
+    pub fn run_main(&mut self) -> Result<i32, String> {
+        let main = match self.procs.iter().find(|p| p.name == "main") {
+            Some(main) => main,
+            None => return Err("There is no main proc to run".to_owned()),
+        };
+        let ret_type = main.ret_type.clone();
+        match ret_type {
+            Type::I32 | Type::N32 | Type::Undefined => (),
+            t => return Err(format!("main must return i32 or nothing, not {:?}", t)),
+        }
+        if self.target_machine.is_null() {
+            self.init_target(self.opt_level)?;
+        }
+
+        unsafe {
+            LLVMLinkInMCJIT();
+            // makes the symbols of the running process (puts and friends) visible to the JIT
+            LLVMLoadLibraryPermanently(ptr::null());
+
+            let mut options = mem::zeroed::<LLVMMCJITCompilerOptions>();
+            LLVMInitializeMCJITCompilerOptions(&mut options, mem::size_of_val(&options));
+            options.OptLevel = match self.opt_level {
+                OptLevel::O0 => 0,
+                OptLevel::O1 => 1,
+                OptLevel::O2 | OptLevel::Os => 2,
+                OptLevel::O3 => 3,
+            };
+            let mut engine = ptr::null_mut();
+            let mut error_msg = ptr::null_mut();
+            if LLVMCreateMCJITCompilerForModule(
+                &mut engine,
+                self.module,
+                &mut options,
+                mem::size_of_val(&options),
+                &mut error_msg,
+            ) == 1 {
+                return Err(take_message(error_msg));
+            }
+
+            let address = LLVMGetFunctionAddress(engine, self.cstr("main"));
+            let status = if address == 0 {
+                Err("Couldn't find main in the compiled module".to_owned())
+            } else if ret_type == Type::Undefined {
+                let main: extern "C" fn() = mem::transmute(address as usize);
+                main();
+                Ok(0)
+            } else {
+                let main: extern "C" fn() -> i32 = mem::transmute(address as usize);
+                Ok(main())
+            };
+
+            // take the module back so that it is only disposed once, in drop
+            let mut module = ptr::null_mut();
+            LLVMRemoveModule(engine, self.module, &mut module, &mut error_msg);
+            LLVMDisposeExecutionEngine(engine);
+            status
         }
     }
 
@@ -660,9 +1245,30 @@ impl<'g> Generator<'g> {
     }
 }
 
+unsafe fn take_message(msg: *mut i8) -> String {
+    let s = CStr::from_ptr(msg).to_string_lossy().into_owned();
+    LLVMDisposeMessage(msg);
+    s
+}
+
+unsafe fn buffer_contents(buffer: LLVMMemoryBufferRef) -> Vec<u8> {
+    let contents = slice::from_raw_parts(
+        LLVMGetBufferStart(buffer) as *const u8,
+        LLVMGetBufferSize(buffer),
+    ).to_vec();
+    LLVMDisposeMemoryBuffer(buffer);
+    contents
+}
+
 impl<'g> Drop for Generator<'g> {
     fn drop(&mut self) {
         unsafe {
+            if !self.target_machine.is_null() {
+                LLVMDisposeTargetMachine(self.target_machine);
+            }
+            if let Some(debug) = &self.debug {
+                LLVMDisposeDIBuilder(debug.builder);
+            }
             LLVMDisposeBuilder(self.builder);
             LLVMDisposeModule(self.module);
             LLVMContextDispose(self.context);
@@ -1,82 +1,300 @@
-#[macro_use]
-extern crate lazy_static;
-
-mod errors;
-mod types;
-
-mod lexer;
-mod parser;
-mod astgen;
-mod ir;
-mod analysis;
-mod llvm;
+mod cli;
+mod link;
 
 use std::io::prelude::*;
-use std::{env, fs};
+use std::io::IsTerminal;
+use std::path::Path;
+use std::{env, fs, io, process};
+
+use cli::{Command, ErrorFormat, Options, Stage};
+use elgin::errors::Diagnostics;
+use elgin::source_map::{FileId, SourceMap};
+use elgin::{checked, codes, doc, ir, lexer, llvm, parser, render};
 
 fn main() {
-    if let Some(_) = env::args().nth(1) {
-        file();
-    } else {
-        panic!("Expected File")
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("error: {}", msg);
+            eprintln!("{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if options.command == Command::Help {
+        print!("{}", cli::help());
+        return;
     }
+    if options.command == Command::Explain {
+        match codes::find(&options.input) {
+            Some(code) => print!("{}", code.explanation),
+            None => {
+                eprintln!("error: {} is not an elgin error code", options.input);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut sources = SourceMap::new();
+    let file = match sources.load(&options.input) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("error: Couldn't read {}: {}", options.input, e);
+            process::exit(1);
+        }
+    };
+
+    let mut diagnostics = Diagnostics::new();
+    for (lint, level) in &options.lints {
+        diagnostics.set_lint_level(lint, *level);
+    }
+    if options.warnings_as_errors {
+        diagnostics.deny_warnings();
+    }
+    let status = compile(&options, &sources, file, &diagnostics);
+    // diagnostics are written to stderr, so that's the stream whose terminal decides on color,
+    // even when stdout is a terminal too (`elgin run` hands stdout to the program)
+    let color = io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let renderer = render::Renderer::new(&sources, color);
+    let mut codes = vec![];
+    for error in diagnostics.into_errors() {
+        match options.error_format {
+            ErrorFormat::Human => eprint!("{}", renderer.render(&error)),
+            ErrorFormat::Json => eprint!("{}", renderer.render_json(&error)),
+        }
+        if let Some(code) = error.code {
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+    }
+    if options.error_format == ErrorFormat::Human {
+        match codes.as_slice() {
+            [] => (),
+            [code] => eprintln!("For more information about this error, try `elgin explain {}`.", code),
+            codes => {
+                eprintln!("Some errors have detailed explanations: {}.", codes.join(", "));
+                eprintln!("For more information about an error, try `elgin explain <code>`.");
+            }
+        }
+    }
+    process::exit(status.unwrap_or(1));
 }
 
-fn file() {
-    let mut file = fs::File::open(env::args().nth(1).unwrap()).unwrap();
-    let mut input = String::new();
-    file.read_to_string(&mut input).unwrap();
-
-    let chars = &input.chars().collect::<Vec<_>>()[..];
-
-    let mut lexer = lexer::Lexer::new(chars);
-    let lex_results_option = lexer.go();
-    println!("______________________");
-    println!("lex errors:");
-    println!("{:#?}", errors::ERRORS.lock().unwrap());
-    let lex_results = lex_results_option.unwrap();
-    println!("______________________");
-    println!("lexer output:");
-    lex_results.iter().map(|t| println!("{:?}", t)).for_each(drop);
-
-    let mut parser = parser::Parser::new(&lex_results);
-    let parse_results = parser.go();
-    println!("______________________");
-    println!("parse errors:");
-    println!("{:#?}", errors::ERRORS.lock().unwrap());
-    println!("______________________");
-    println!("parser output:");
-    println!("{:#?}", parse_results);
-
-    let unwrapped = parse_results.unwrap();
-    let mut irbuilder = ir::IRBuilder::new(&unwrapped, parser.available_type_var);
-    let ir_results = irbuilder.go();
-    println!("______________________");
-    println!("IR gen errors:");
-    println!("{:#?}", errors::ERRORS.lock().unwrap());
-    println!("______________________");
-    println!("IR output:");
-    println!("{:#?}", *ir_results.unwrap());
-
-    println!("______________________");
-    println!("analysis output:");
-    let analysis_option = irbuilder.analyze();
-    println!("______________________");
-    println!("analysis errors:");
-    println!("{:#?}", errors::ERRORS.lock().unwrap());
-    analysis_option.unwrap();
-
-    let mut generator = llvm::Generator::new(&irbuilder.procs, "elgin", &env::args().nth(1).unwrap());
-    generator.go();
-    println!("______________________");
-    println!("codegen output:");
-    println!("Dumping to file...");
-    let mut file_name = env::args().nth(1).unwrap();
-    file_name.push_str(".ll");
-    generator.dump_to_file(&file_name);
-    println!("File done!");
-
-    println!("______________________");
-    println!("Errors:");
-    println!("{:#?}", errors::ERRORS.lock().unwrap());
+/// Runs as much of the pipeline as the command needs and returns the exit status
+fn compile(options: &Options, sources: &SourceMap, file: FileId, diagnostics: &Diagnostics) -> Option<i32> {
+    let mut lexer = lexer::Lexer::new(sources.get(file).source(), file, diagnostics);
+    // the parser still runs after lexer errors, to report its own errors too
+    let tokens = lexer.go()?;
+    if options.emits(Stage::Tokens) || options.done_after(Stage::Tokens) {
+        checked(Some(()), diagnostics)?;
+    }
+    if options.emits(Stage::Tokens) {
+        let dump: String = tokens.iter().map(|t| format!("{:?}\n", t)).collect();
+        write_artifact(options, Stage::Tokens, dump.as_bytes())?;
+    }
+    if options.done_after(Stage::Tokens) {
+        return Some(0);
+    }
+
+    let mut parser = parser::Parser::new(&tokens, file, diagnostics);
+    let ast = checked(parser.go(), diagnostics)?;
+    if options.emits(Stage::Ast) {
+        write_artifact(options, Stage::Ast, format!("{:#?}\n", ast).as_bytes())?;
+    }
+    if options.done_after(Stage::Ast) {
+        return Some(0);
+    }
+    if options.command == Command::Doc {
+        return write_docs(options, &doc::collect(&ast));
+    }
+    if options.command == Command::Test {
+        return doc_tests(options, &doc::tests(sources.get(file), &ast));
+    }
+
+    let mut irbuilder = ir::IRBuilder::new(&ast, parser.available_type_var, diagnostics);
+    irbuilder.verbose = options.verbose;
+    checked(irbuilder.go(), diagnostics)?;
+    checked(irbuilder.analyze(), diagnostics)?;
+    irbuilder.lint();
+    checked(Some(()), diagnostics)?;
+    if options.emits(Stage::Eir) {
+        write_artifact(options, Stage::Eir, format!("{:#?}\n", irbuilder.procs).as_bytes())?;
+    }
+    if options.command == Command::Check || options.done_after(Stage::Eir) {
+        return Some(0);
+    }
+
+    let mut generator = llvm::Generator::new(&irbuilder.procs, diagnostics, "elgin", &options.input);
+    if let Err(e) = generator.init_target(options.opt_level) {
+        eprintln!("error: Couldn't set up the target machine: {}", e);
+        return None;
+    }
+    if options.debug_info {
+        generator.enable_debug_info(sources.get(file));
+    }
+    checked(generator.go(), diagnostics)?;
+    generator.optimize();
+    if options.emits(Stage::LlvmIr) {
+        write_artifact(options, Stage::LlvmIr, generator.ir_string().as_bytes())?;
+    }
+    if options.emits(Stage::Bitcode) {
+        write_artifact(options, Stage::Bitcode, &generator.bitcode())?;
+    }
+    for (stage, file_type) in &[(Stage::Asm, llvm::FileType::Assembly), (Stage::Obj, llvm::FileType::Object)] {
+        if options.emits(*stage) {
+            match generator.native(*file_type) {
+                Ok(contents) => write_artifact(options, *stage, &contents)?,
+                Err(e) => {
+                    eprintln!("error: Couldn't generate native code: {}", e);
+                    return None;
+                }
+            };
+        }
+    }
+    match options.command {
+        Command::Build => {
+            let output = options.output.clone().unwrap_or_else(|| options.default_executable());
+            build(&mut generator, &output)
+        }
+        Command::Run => run(&mut generator),
+        _ => Some(0),
+    }
+}
+
+fn write_artifact(options: &Options, stage: Stage, contents: &[u8]) -> Option<i32> {
+    match options.emit_path(stage) {
+        Some(path) => write_result(&path, fs::write(&path, contents).map_err(|e| e.to_string())),
+        None => write_result("stdout", io::stdout().write_all(contents).map_err(|e| e.to_string())),
+    }
+}
+
+/// Writes the documentation as `index.html` and `index.md` in the output directory
+fn write_docs(options: &Options, items: &[doc::Item]) -> Option<i32> {
+    let dir = options.output.clone().unwrap_or_else(|| options.default_output("doc"));
+    write_result(&dir, fs::create_dir_all(&dir).map_err(|e| e.to_string()))?;
+    let title = Path::new(&options.input).file_stem().map_or(options.input.clone(), |s| s.to_string_lossy().into_owned());
+    for (name, contents) in &[("index.html", doc::html(&title, items)), ("index.md", doc::markdown(&title, items))] {
+        let path = Path::new(&dir).join(name).to_string_lossy().into_owned();
+        write_result(&path, fs::write(&path, contents).map_err(|e| e.to_string()))?;
+    }
+    Some(0)
+}
+
+/// Runs each doc-test through `elgin run` (or `elgin build` for `no_run`), and reports how they went
+fn doc_tests(options: &Options, tests: &[doc::DocTest]) -> Option<i32> {
+    let exe = match env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("error: Couldn't find the elgin executable: {}", e);
+            return None;
+        }
+    };
+    println!("\nrunning {} doc-test{}", tests.len(), if tests.len() == 1 { "" } else { "s" });
+    let (mut passed, mut ignored, mut failures) = (0, 0, vec![]);
+    for (i, test) in tests.iter().enumerate() {
+        let name = format!("{} - {}", options.input, test.name);
+        if test.mode == doc::DocTestMode::Ignore {
+            println!("test {} ... ignored", name);
+            ignored += 1;
+            continue;
+        }
+        let path = env::temp_dir().join(format!("elgin-doctest-{}-{}.eln", process::id(), i));
+        let executable = path.with_extension("");
+        write_result(&path.to_string_lossy(), fs::write(&path, &test.source).map_err(|e| e.to_string()))?;
+        let mut command = process::Command::new(&exe);
+        match test.mode {
+            doc::DocTestMode::NoRun => command.arg("build").arg("-o").arg(&executable),
+            _ => command.arg("run"),
+        };
+        let output = command.arg(options.opt_level.as_flag()).arg(&path).output();
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&executable);
+        match output {
+            Ok(output) if output.status.success() => {
+                println!("test {} ... ok", name);
+                passed += 1;
+            }
+            Ok(output) => {
+                println!("test {} ... FAILED", name);
+                let status = match output.status.code() {
+                    Some(code) => format!("exited with status {}", code),
+                    None => "was killed by a signal".to_owned(),
+                };
+                let mut report = format!("the test {}\n", status);
+                report.push_str(&String::from_utf8_lossy(&output.stdout));
+                report.push_str(&String::from_utf8_lossy(&output.stderr));
+                failures.push((name, report));
+            }
+            Err(e) => {
+                println!("test {} ... FAILED", name);
+                failures.push((name, format!("couldn't start elgin: {}\n", e)));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (name, report) in &failures {
+            print!("\n---- {} ----\n{}", name, report);
+        }
+    }
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} ignored\n",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        passed,
+        failures.len(),
+        ignored,
+    );
+    Some(if failures.is_empty() { 0 } else { 1 })
+}
+
+fn write_result(path: &str, result: Result<(), String>) -> Option<i32> {
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("error: Couldn't write {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Writes the module to a temporary object file and links it into an executable at `output`
+fn build(generator: &mut llvm::Generator, output: &str) -> Option<i32> {
+    let object = match generator.native(llvm::FileType::Object) {
+        Ok(object) => object,
+        Err(e) => {
+            eprintln!("error: Couldn't generate native code: {}", e);
+            return None;
+        }
+    };
+    let path = env::temp_dir().join(format!("elgin-build-{}.o", process::id()));
+    let path = match path.to_str() {
+        Some(path) => path.to_owned(),
+        None => {
+            eprintln!("error: Couldn't write the object file to {}, as the path isn't valid UTF-8", path.display());
+            return None;
+        }
+    };
+    write_result(&path, fs::write(&path, object).map_err(|e| e.to_string()))?;
+    let result = link::link(&[&path], output);
+    let _ = fs::remove_file(&path);
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("error: Couldn't link {}: {}", output, e);
+            None
+        }
+    }
+}
+
+fn run(generator: &mut llvm::Generator) -> Option<i32> {
+    match generator.run_main() {
+        Ok(status) => Some(status),
+        Err(e) => {
+            eprintln!("error: Couldn't run the program: {}", e);
+            None
+        }
+    }
 }
@@ -1,54 +1,70 @@
 //! Parser
 
-use crate::errors::{Logger, Span};
+use crate::errors::{Diagnostics, Span};
 use crate::lexer::Token;
+use crate::source_map::FileId;
 use crate::types::Type;
 
 pub struct Parser<'p> {
     pub tokens: &'p [Span<Token>],
+    pub file: FileId,
+    pub diagnostics: &'p Diagnostics,
     pub index: usize,
     pub available_type_var: usize,
+    /// Doc comments waiting for the proc or const after them
+    pub docs: String,
+    /// How many expressions and blocks the parser is inside of
+    pub depth: usize,
 }
 
 impl<'p> Parser<'p> {
-    pub fn new(tokens: &'p [Span<Token>]) -> Self {
+    pub fn new(tokens: &'p [Span<Token>], file: FileId, diagnostics: &'p Diagnostics) -> Self {
         Parser { 
             tokens, 
+            file,
+            diagnostics,
             index: 0,
             available_type_var: 0,
+            docs: String::new(),
+            depth: 0,
         }
     }
 
     pub fn next(&mut self) -> Span<Token> {
-        self.index += 1;
-        if self.index >= self.tokens.len() {
-            let last = self.tokens.last().unwrap();
-            return Span {
-                contents: Token::EOF,
-                pos: last.pos,
-                len: last.len,
-            };
+        let token = self.peek();
+        if self.index < self.tokens.len() {
+            self.index += 1;
         }
-        self.tokens[self.index - 1].clone() }
+        token
+    }
+
     pub fn peek(&mut self) -> Span<Token> {
         if self.index >= self.tokens.len() {
-            let last = self.tokens.last().unwrap();
-            return Span {
-                contents: Token::EOF,
-                pos: last.pos,
-                len: last.len,
-            };
+            return self.eof();
         }
         self.tokens[self.index].clone()
     }
 
+    /// An EOF token at the last token, so errors at the end of the file point somewhere
+    fn eof(&self) -> Span<Token> {
+        let (pos, len) = self.tokens.last().map_or((0, 0), |last| (last.pos, last.len));
+        Span {
+            contents: Token::EOF,
+            file: self.file,
+            pos,
+            len,
+        }
+    }
+
     pub fn ensure_next(&mut self, t: Token) -> Option<()> {
         if self.peek().contents == t {
             self.next();
             Some(())
         } else {
-            Logger::syntax_error(
+            self.diagnostics.syntax_error(
+                "E0002",
                 format!("Expected a {:?} token, but found a {:?} instead", t, self.peek().contents.clone()).as_str(),
+                self.file,
                 self.peek().pos,
                 self.peek().len,
             );
@@ -70,8 +86,10 @@ impl<'p> Parser<'p> {
             self.next();
             Some(id)
         } else {
-            Logger::syntax_error(
+            self.diagnostics.syntax_error(
+                "E0003",
                 format!("Expected an identifier, but found a {:?} token instead", self.peek().contents.clone()).as_str(),
+                self.file,
                 self.peek().pos,
                 self.peek().len,
             );
@@ -102,8 +120,10 @@ impl<'p> Parser<'p> {
                     "bool" => Type::Bool,
 
                     _ => {
-                        Logger::syntax_error(
+                        self.diagnostics.syntax_error(
+                            "E0004",
                             format!("Expected a type, but found a {:?} instead", self.peek().contents.clone()).as_str(),
+                            self.file,
                             self.peek().pos,
                             self.peek().len,
                         );
@@ -120,14 +140,28 @@ impl<'p> Parser<'p> {
             },
             Token::LBracket => {
                 self.next(); // skip the LBracket
-                if let Token::IntLiteral(size) = self.peek().contents {
-                    self.next();
+                if let Token::IntLiteral(size, _) = self.peek().contents {
+                    let size_token = self.next();
                     self.ensure_next(Token::RBracket)?;
                     let content_type = self.ensure_type()?; 
-                    Some(Type::Array(size.parse().unwrap(), Box::new(content_type)))
+                    match size.parse() {
+                        Ok(size) => Some(Type::Array(size, Box::new(content_type))),
+                        Err(_) => {
+                            self.diagnostics.syntax_error(
+                                "E0005",
+                                format!("The length of an array has to fit in 64 bits, but {} doesn't", size).as_str(),
+                                self.file,
+                                size_token.pos,
+                                size_token.len,
+                            );
+                            None
+                        }
+                    }
                 } else {
-                    Logger::syntax_error(
+                    self.diagnostics.syntax_error(
+                        "E0005",
                         format!("Expect an integer as the length of an array, but found a {:?} token instead", self.peek().contents).as_str(),
+                        self.file,
                         self.peek().pos,
                         self.peek().len,
                     );
@@ -135,8 +169,10 @@ impl<'p> Parser<'p> {
                 }
             },
             _ => {
-                Logger::syntax_error(
+                self.diagnostics.syntax_error(
+                    "E0004",
                     format!("Expected a type, but found a {:?} instead", self.peek().contents.clone()).as_str(),
+                    self.file,
                     self.peek().pos,
                     self.peek().len,
                 );
@@ -145,6 +181,14 @@ impl<'p> Parser<'p> {
         }
     }
 
+    /// The length of the source from `pos` to the end of the last token consumed
+    pub fn len_since(&self, pos: usize) -> usize {
+        match self.index.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(last) => (last.pos + last.len).saturating_sub(pos),
+            None => 0,
+        }
+    }
+
     pub fn next_type_var(&mut self) -> usize {
         self.available_type_var += 1;
         self.available_type_var - 1
@@ -0,0 +1,222 @@
+//! Rendering errors along with the source code they point at
+
+use std::fmt::Write;
+
+use crate::errors::{Error, ErrorType, Severity};
+use crate::source_map::{SourceFile, SourceMap};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// A mark under part of a source line, with the message to show next to it
+struct Marker<'a> {
+    line: usize,
+    col: usize,
+    len: usize,
+    msg: &'a str,
+    primary: bool,
+}
+
+pub struct Renderer<'a> {
+    sources: &'a SourceMap,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(sources: &'a SourceMap, color: bool) -> Self {
+        Renderer { sources, color }
+    }
+
+    pub fn render(&self, error: &Error) -> String {
+        let mut out = String::new();
+        let (kind, style) = match (error.severity, &error.typ) {
+            (Severity::Note, _) => ("note", BOLD),
+            (Severity::Warning, _) => ("warning", YELLOW),
+            (Severity::Error, ErrorType::SyntaxError) => ("syntax error", RED),
+            (Severity::Error, ErrorType::NameError) => ("name error", RED),
+            (Severity::Error, ErrorType::TypeError) => ("type error", RED),
+            (Severity::Error, ErrorType::InternalError) => ("internal compiler error", RED),
+            (Severity::Error, _) => ("error", RED),
+        };
+        let kind = match error.code {
+            Some(code) => format!("{}[{}]", kind, code),
+            None => kind.to_owned(),
+        };
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            self.paint(style, &kind),
+            self.paint(BOLD, &error.msg),
+        );
+
+        let file = self.sources.get(error.file);
+        let mut markers = vec![marker(file, error.pos, error.len, "", true)];
+        for label in &error.labels {
+            markers.push(marker(file, label.pos, label.len, &label.msg, false));
+        }
+        markers.sort_by_key(|m| (m.line, !m.primary, m.col));
+
+        let (line, col) = file.line_col(error.pos);
+        let width = markers.iter().map(|m| m.line).max().unwrap_or(line).to_string().len();
+        let gutter = " ".repeat(width);
+        let _ = writeln!(out, "{}{} {}:{}:{}", gutter, self.paint(BLUE, "-->"), file.name(), line, col);
+        let _ = writeln!(out, "{} {}", gutter, self.paint(BLUE, "|"));
+
+        let mut last_line = None;
+        for marker in &markers {
+            if last_line != Some(marker.line) {
+                if let Some(last) = last_line {
+                    if marker.line > last + 1 {
+                        let _ = writeln!(out, "{}", self.paint(BLUE, "..."));
+                    }
+                }
+                let number = format!("{:>width$} |", marker.line, width = width);
+                let _ = writeln!(out, "{} {}", self.paint(BLUE, &number), file.line(marker.line));
+                last_line = Some(marker.line);
+            }
+            let (style, mark) = if marker.primary { (style, "^") } else { (BLUE, "-") };
+            let mut underline = mark.repeat(marker.len);
+            if !marker.msg.is_empty() {
+                underline.push(' ');
+                underline.push_str(marker.msg);
+            }
+            let _ = writeln!(
+                out,
+                "{} {} {}{}",
+                gutter,
+                self.paint(BLUE, "|"),
+                indent(file.line(marker.line), marker.col),
+                self.paint(style, &underline),
+            );
+        }
+
+        if !error.notes.is_empty() || error.help.is_some() || !error.suggestions.is_empty() {
+            let _ = writeln!(out, "{} {}", gutter, self.paint(BLUE, "|"));
+        }
+        for note in &error.notes {
+            let _ = writeln!(out, "{} {} {}: {}", gutter, self.paint(BLUE, "="), self.paint(BOLD, "note"), note);
+        }
+        if let Some(help) = &error.help {
+            let _ = writeln!(out, "{} {} {}: {}", gutter, self.paint(BLUE, "="), self.paint(BOLD, "help"), help);
+        }
+        for suggestion in &error.suggestions {
+            let _ = writeln!(out, "{} {} {}: {}", gutter, self.paint(BLUE, "="), self.paint(BOLD, "help"), suggestion.msg);
+        }
+        out
+    }
+
+    /// Renders an error as a single line of JSON, for editors and other tools
+    pub fn render_json(&self, error: &Error) -> String {
+        let file = self.sources.get(error.file);
+        let mut spans = vec![json_span(file, error.pos, error.len, None, true)];
+        for label in &error.labels {
+            spans.push(json_span(file, label.pos, label.len, Some(&label.msg), false));
+        }
+        let suggestions: Vec<_> = error
+            .suggestions
+            .iter()
+            .map(|s| {
+                format!(
+                    "{{\"message\":{},\"span\":{},\"replacement\":{}}}",
+                    json_string(&s.msg),
+                    json_span(file, s.pos, s.len, None, false),
+                    json_string(&s.replacement),
+                )
+            })
+            .collect();
+        let notes: Vec<_> = error.notes.iter().map(|n| json_string(n)).collect();
+        let severity = match error.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        };
+        format!(
+            "{{\"type\":\"{:?}\",\"severity\":\"{}\",\"lint\":{},\"code\":{},\"message\":{},\"file\":{},\
+             \"spans\":[{}],\"notes\":[{}],\"help\":{},\"suggestions\":[{}]}}\n",
+            error.typ,
+            severity,
+            error.lint.map_or("null".to_owned(), json_string),
+            error.code.map_or("null".to_owned(), json_string),
+            json_string(&error.msg),
+            json_string(file.name()),
+            spans.join(","),
+            notes.join(","),
+            error.help.as_deref().map_or("null".to_owned(), json_string),
+            suggestions.join(","),
+        )
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+/// A span as JSON, with 1-based lines and columns and an exclusive end
+fn json_span(file: &SourceFile, pos: usize, len: usize, label: Option<&str>, primary: bool) -> String {
+    let (line_start, column_start) = file.line_col(pos);
+    let (line_end, column_end) = file.line_col(pos + len);
+    format!(
+        "{{\"line_start\":{},\"column_start\":{},\"line_end\":{},\"column_end\":{},\
+         \"is_primary\":{},\"label\":{}}}",
+        line_start,
+        column_start,
+        line_end,
+        column_end,
+        primary,
+        label.map_or("null".to_owned(), json_string),
+    )
+}
+
+/// Works out where a span goes in characters, cutting it off at the end of its first line
+fn marker<'m>(file: &SourceFile, pos: usize, len: usize, msg: &'m str, primary: bool) -> Marker<'m> {
+    let (line, col) = file.line_col(pos);
+    let len = match file.line_col(pos + len) {
+        (end_line, end_col) if end_line == line => end_col - col,
+        _ => file.line(line).chars().count().saturating_sub(col - 1),
+    };
+    Marker {
+        line,
+        col,
+        len: len.max(1),
+        msg,
+        primary,
+    }
+}
+
+/// Whitespace lining a marker up with column `col`, keeping the line's tabs
+fn indent(text: &str, col: usize) -> String {
+    let mut indent: String = text
+        .chars()
+        .take(col - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let missing = (col - 1).saturating_sub(indent.chars().count());
+    indent.push_str(&" ".repeat(missing));
+    indent
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
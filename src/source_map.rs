@@ -0,0 +1,141 @@
+//! The source files loaded for a compilation, and positions within them
+
+use std::fs;
+use std::io;
+
+/// Identifies a file in a `SourceMap`
+///
+/// The default is the first file loaded, which is also where builtins claim to live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(usize);
+
+/// Owns every file loaded for a compilation
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &str, source: String) -> FileId {
+        self.files.push(SourceFile::new(name, source));
+        FileId(self.files.len() - 1)
+    }
+
+    /// Reads a file from disk and adds it, under its path
+    pub fn load(&mut self, path: &str) -> io::Result<FileId> {
+        let source = fs::read_to_string(path)?;
+        Ok(self.add(path, source))
+    }
+
+    pub fn get(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0]
+    }
+}
+
+/// A source file, along with where each of its lines starts
+///
+/// Positions are byte offsets into the source, unless they are called
+/// character indexes. Lines and columns are 1-based, and columns count
+/// characters rather than bytes.
+#[derive(Debug)]
+pub struct SourceFile {
+    name: String,
+    source: String,
+    /// The byte offset of the start of each line
+    line_starts: Vec<usize>,
+    /// The character index of the start of each line
+    line_char_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(name: &str, source: String) -> Self {
+        let mut line_starts = vec![0];
+        let mut line_char_starts = vec![0];
+        for (i, (offset, ch)) in source.char_indices().enumerate() {
+            if ch == '\n' {
+                line_starts.push(offset + 1);
+                line_char_starts.push(i + 1);
+            }
+        }
+        SourceFile {
+            name: name.to_owned(),
+            source,
+            line_starts,
+            line_char_starts,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The line and column of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = self.floor_char_boundary(offset);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let col = self.source[self.line_starts[line]..offset].chars().count();
+        (line + 1, col + 1)
+    }
+
+    /// The byte offset of a line and column, which stops at the end of the line
+    pub fn offset(&self, line: usize, col: usize) -> usize {
+        let start = self.line_start(line);
+        let text = self.line(line);
+        start + text.char_indices().nth(col.saturating_sub(1)).map_or(text.len(), |(offset, _)| offset)
+    }
+
+    /// The byte offset of the first character of a line
+    pub fn line_start(&self, line: usize) -> usize {
+        self.line_starts[line - 1]
+    }
+
+    /// The text of a line, without its line break
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.line_start(line);
+        let end = self.line_starts.get(line).map_or(self.source.len(), |next| next - 1);
+        &self.source[start..end]
+    }
+
+    /// The byte offset of the character at `index`, or the end of the file past it
+    pub fn char_to_byte(&self, index: usize) -> usize {
+        let line = match self.line_char_starts.binary_search(&index) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let start = self.line_starts[line];
+        self.source[start..]
+            .char_indices()
+            .nth(index - self.line_char_starts[line])
+            .map_or(self.source.len(), |(offset, _)| start + offset)
+    }
+
+    /// The index of the character at a byte offset
+    pub fn byte_to_char(&self, offset: usize) -> usize {
+        let offset = self.floor_char_boundary(offset);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        self.line_char_starts[line] + self.source[self.line_starts[line]..offset].chars().count()
+    }
+
+    /// Moves an offset back onto the start of a character, and into the file
+    fn floor_char_boundary(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
@@ -36,6 +36,28 @@ pub enum Type {
     Array(usize, Box<Type>),
 }
 
+impl Type {
+    /// Whether inference has finished with the type, leaving no type variables or untyped literals in it
+    pub fn is_resolved(&self) -> bool {
+        match self {
+            Type::Variable(_) | Type::IntLiteral | Type::FloatLiteral => false,
+            Type::Ptr(t) | Type::Array(_, t) => t.is_resolved(),
+            _ => true,
+        }
+    }
+
+    /// Whether an integer literal can have the type
+    pub fn is_integer(&self) -> bool {
+        use Type::*;
+        matches!(self, IntLiteral | I8 | I16 | I32 | I64 | I128 | N8 | N16 | N32 | N64 | N128)
+    }
+
+    /// Whether a float literal can have the type
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::FloatLiteral | Type::F32 | Type::F64 | Type::F128)
+    }
+}
+
 impl fmt::Debug for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Type::*;
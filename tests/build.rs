@@ -0,0 +1,33 @@
+//! Checks the executables written by `elgin build`
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+/// Builds `example` into a temporary executable and runs it, giving its exit code and stdout
+fn build_and_run(example: &str, args: &[&str]) -> (i32, String) {
+    let executable = env::temp_dir().join(format!("elgin-build-{}-{}", example, std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin"))
+        .arg("build")
+        .args(args)
+        .arg(format!("examples/{}.eln", example))
+        .arg("-o")
+        .arg(&executable)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = Command::new(&executable).output().unwrap();
+    let _ = fs::remove_file(&executable);
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn exit_code() {
+    assert_eq!(build_and_run("factorial", &[]), (120, String::new()));
+    assert_eq!(build_and_run("factorial", &["-O2"]), (120, String::new()));
+}
+
+#[test]
+fn stdout() {
+    assert_eq!(build_and_run("hello", &[]), (0, "Hello world!\n".to_owned()));
+}
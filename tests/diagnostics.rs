@@ -0,0 +1,208 @@
+//! Checks how errors are rendered by `elgin check`
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn check(name: &str, source: &str, args: &[&str], status: i32) -> (String, String) {
+    let path = env::temp_dir().join(format!("elgin-diagnostics-{}-{}.eln", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin"))
+        .arg("check")
+        .args(args)
+        .arg(&path)
+        .env_remove("NO_COLOR")
+        .output()
+        .unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(output.status.code(), Some(status));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (stderr, path.to_string_lossy().into_owned())
+}
+
+#[test]
+fn unterminated_string() {
+    let (stderr, path) = check("string", "proc main() {\n    puts(\"hi\n}\n", &[], 1);
+    let expected = format!(
+        "\
+syntax error[E0001]: Encountered end of file while parsing string literal
+ --> {}:2:10
+  |
+2 |     puts(\"hi
+  |          ^^^
+  |
+  = help: add a closing `\"` to end the string
+For more information about this error, try `elgin explain E0001`.
+",
+        path,
+    );
+    assert_eq!(stderr, expected);
+}
+
+#[test]
+fn unclosed_block() {
+    let (stderr, path) = check("block", "proc main() {\n    var x = 1\n\n    x = 2 ,\n}\n", &[], 1);
+    let expected = format!(
+        "\
+syntax error[E0002]: Expected a RBrace token, but found a Comma instead
+ --> {}:4:11
+  |
+1 | proc main() {{
+  |             - the block starts here
+...
+4 |     x = 2 ,
+  |           ^
+For more information about this error, try `elgin explain E0002`.
+",
+        path,
+    );
+    assert_eq!(stderr, expected);
+}
+
+#[test]
+fn top_level_statement() {
+    let (stderr, _) = check("top-level", "var x = 1\n", &[], 1);
+    assert!(stderr.contains("1 | var x = 1\n  | ^^^^^^^^^\n"), "{}", stderr);
+    assert!(stderr.contains("  = note: only procs and consts can appear at the top level\n"));
+    assert!(stderr.contains("  = help: move it into the body of a proc, such as main\n"));
+}
+
+#[test]
+fn json() {
+    let (stderr, path) = check("json", "proc main() {\n    puts(\"hi\n", &["--error-format=json"], 1);
+    let expected = format!(
+        "{{\"type\":\"SyntaxError\",\"severity\":\"error\",\"lint\":null,\"code\":\"E0001\",\
+         \"message\":\"Encountered end of file while parsing string literal\",\"file\":\"{}\",\
+         \"spans\":[{{\"line_start\":2,\"column_start\":10,\"line_end\":3,\"column_end\":1,\
+         \"is_primary\":true,\"label\":null}}],\"notes\":[],\"help\":null,\
+         \"suggestions\":[{{\"message\":\"add a closing `\\\"` to end the string\",\
+         \"span\":{{\"line_start\":3,\"column_start\":1,\"line_end\":3,\"column_end\":1,\
+         \"is_primary\":false,\"label\":null}},\"replacement\":\"\\\"\"}}]}}\n",
+        path,
+    );
+    assert_eq!(stderr, expected);
+}
+
+#[test]
+fn unused_variable_lint() {
+    let source = "proc main(): i32 {\n    var x: i32 = 1\n    var _y: i32 = 2\n    return 0\n}\n";
+    let (stderr, path) = check("lint", source, &[], 0);
+    let expected = format!(
+        "\
+warning: The variable x is never read
+ --> {}:2:5
+  |
+2 |     var x: i32 = 1
+  |     ^^^^^^^^^^^^^^
+  |
+  = note: `-W unused_variables` is on by default
+  = help: if this is intentional, name it _x instead
+",
+        path,
+    );
+    assert_eq!(stderr, expected);
+
+    let (stderr, _) = check("lint-allow", source, &["-A", "unused-variables"], 0);
+    assert_eq!(stderr, "");
+    let (stderr, _) = check("lint-deny", source, &["-Dunused_variables"], 1);
+    assert!(stderr.starts_with("error: The variable x is never read\n"), "{}", stderr);
+    let (stderr, _) = check("lint-werror", source, &["-Werror"], 1);
+    assert!(stderr.contains("  = note: warnings are treated as errors because of `-Werror`\n"), "{}", stderr);
+}
+
+#[test]
+fn emit_without_codegen() {
+    let source = "proc main(): i32 {\n    return 0\n}\n";
+    let (stderr, _) = check("emit-llvm-ir", source, &["--emit=eir,llvm-ir"], 2);
+    assert!(
+        stderr.starts_with("error: `elgin check` doesn't generate any code, so it can only emit tokens, ast or eir\n"),
+        "{}",
+        stderr
+    );
+    let (stderr, _) = check("emit-eir", source, &["--emit=eir", "-o", "/dev/null"], 0);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn explain() {
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin")).args(["explain", "E0002"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("The parser needed one particular token next"), "{}", stdout);
+    assert!(stdout.contains("Erroneous code example:"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin")).args(["explain", "E9999"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn similar_names() {
+    let source = "proc main(): i32 {\n    var count = 1\n    return cuont + fibonnaci(2)\n}\nproc fibonacci(n: i32): i32 {\n    return n\n}\n";
+    let (stderr, _) = check("similar", source, &[], 1);
+    assert!(stderr.contains("3 |     return cuont + fibonnaci(2)\n  |            ^^^^^\n"), "{}", stderr);
+    assert!(stderr.contains("  = help: a variable with a similar name exists: `count`\n"), "{}", stderr);
+
+    // the locals of another proc are out of scope, so they're neither found nor suggested
+    for name in ["totl", "total"] {
+        let source = format!("proc f(): i32 {{\n    var total = 1\n    return total\n}}\nproc main(): i32 {{\n    return {}\n}}\n", name);
+        let (stderr, _) = check("similar-other-proc", &source, &[], 1);
+        assert!(stderr.contains(&format!("Can't find a variable named {} ", name)), "{}", stderr);
+        assert!(!stderr.contains("similar name"), "{}", stderr);
+    }
+
+    let source = "proc main(): i32 {\n    return fibonnaci(2)\n}\nproc fibonacci(n: i32): i32 {\n    return n\n}\n";
+    let (stderr, _) = check("similar-json", source, &["--error-format=json"], 1);
+    assert!(
+        stderr.contains(
+            "\"suggestions\":[{\"message\":\"a proc with a similar name exists: `fibonacci`\",\
+             \"span\":{\"line_start\":2,\"column_start\":12,\"line_end\":2,\"column_end\":21,\
+             \"is_primary\":false,\"label\":null},\"replacement\":\"fibonacci\"}]"
+        ),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn unicode_columns() {
+    let source = "proc main(): i32 {\n    var café = 1\n    return café + cafe\n}\n";
+    let (stderr, path) = check("unicode", source, &[], 1);
+    assert!(stderr.contains(&format!(" --> {}:3:19\n", path)), "{}", stderr);
+    assert!(stderr.contains("3 |     return café + cafe\n  |                   ^^^^\n"), "{}", stderr);
+    assert!(stderr.contains("  = help: a variable with a similar name exists: `café`\n"), "{}", stderr);
+}
+
+#[test]
+fn recovery() {
+    let source = "\
+proc main(): i32 {
+    var x = )
+    x = 2 ,
+    return x
+}
+
+const c: int = 1
+
+proc f() {
+    if 1 < 2 {
+        puts(\"hi\"
+    }
+
+proc g(): i32 {
+    return *
+}
+";
+    let (stderr, _) = check("recovery", source, &[], 1);
+    let headers: Vec<_> = stderr.lines().filter(|l| l.starts_with("syntax error")).collect();
+    assert_eq!(
+        headers,
+        [
+            "syntax error[E0012]: Expected an expression, but found a RParen token instead",
+            "syntax error[E0002]: Expected a RBrace token, but found a Comma instead",
+            "syntax error[E0004]: Expected a type, but found a Ident(\"int\") instead",
+            "syntax error[E0002]: Expected a RParen token, but found a RBrace instead",
+            "syntax error[E0012]: Expected an expression, but found a Op(\"*\") token instead",
+            "syntax error[E0002]: Expected a RBrace token, but found a EOF instead",
+        ]
+    );
+}
@@ -0,0 +1,178 @@
+//! Checks the documentation written by `elgin doc` and the doc-tests run by `elgin test --doc`
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use elgin::doc::{self, DocTestMode, ItemKind};
+use elgin::errors::Diagnostics;
+use elgin::lexer::Lexer;
+use elgin::parser::Parser;
+use elgin::source_map::SourceMap;
+
+const SOURCE: &str = "\
+#: The answer, for `twice` to double
+const answer: i32 = 21
+
+#: Doubles `n`, see also `answer`
+#:
+#: ```
+#: return twice(answer)
+#: ```
+proc twice(n: i32): i32 {
+    return add(n, n)
+}
+
+#: Adds <a> & <b>
+proc add(a: i32, b: i32): i32 {
+    return a + b
+}
+
+proc main(): i32 {
+    return twice(answer)
+}
+";
+
+fn items(source: &str) -> Vec<doc::Item> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("test.eln", source.to_owned());
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(sources.get(file).source(), file, &diagnostics).go().unwrap();
+    let ast = Parser::new(&tokens, file, &diagnostics).go().unwrap();
+    assert!(!diagnostics.has_errors());
+    doc::collect(&ast)
+}
+
+fn tests(source: &str) -> Vec<doc::DocTest> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("test.eln", source.to_owned());
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(sources.get(file).source(), file, &diagnostics).go().unwrap();
+    let ast = Parser::new(&tokens, file, &diagnostics).go().unwrap();
+    doc::tests(sources.get(file), &ast)
+}
+
+fn elgin_test(name: &str, source: &str, status: i32) -> String {
+    let path = env::temp_dir().join(format!("elgin-doc-{}-{}.eln", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin")).args(["test", "--doc"]).arg(&path).output().unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(output.status.code(), Some(status), "{}", String::from_utf8_lossy(&output.stdout));
+    String::from_utf8(output.stdout).unwrap().replace(&*path.to_string_lossy(), "test.eln")
+}
+
+#[test]
+fn collect() {
+    let items = items(SOURCE);
+    let names: Vec<_> = items.iter().map(|i| (i.kind, i.name.as_str())).collect();
+    assert_eq!(names, [(ItemKind::Const, "answer"), (ItemKind::Proc, "twice"), (ItemKind::Proc, "add")]);
+    assert_eq!(items[0].signature, "const answer: i32");
+    assert_eq!(items[1].signature, "proc twice(n: i32): i32");
+    assert_eq!(items[1].docs, "Doubles `n`, see also `answer`\n\n```\nreturn twice(answer)\n```");
+    assert_eq!(items[1].calls, ["add"]);
+    assert_eq!(items[2].signature, "proc add(a: i32, b: i32): i32");
+    assert!(items[2].calls.is_empty());
+}
+
+#[test]
+fn markdown() {
+    let markdown = doc::markdown("test", &items(SOURCE));
+    assert!(markdown.starts_with("# test\n\n<a id=\"answer\"></a>\n## answer\n\n```\nconst answer: i32\n```\n\n"));
+    assert!(markdown.contains("The answer, for [`twice`](#twice) to double\n"), "{}", markdown);
+    assert!(markdown.contains("Doubles `n`, see also [`answer`](#answer)\n"), "{}", markdown);
+    // code blocks are left alone
+    assert!(markdown.contains("```\nreturn twice(answer)\n```\n"), "{}", markdown);
+    assert!(markdown.contains("\nCalls [`add`](#add).\n"), "{}", markdown);
+}
+
+#[test]
+fn html() {
+    let html = doc::html("test", &items(SOURCE));
+    assert!(html.contains("<li><a href=\"#twice\"><code>twice</code></a></li>"), "{}", html);
+    assert!(html.contains("<section class=\"proc\" id=\"twice\">"), "{}", html);
+    assert!(html.contains("<pre class=\"signature\"><code>proc twice(n: i32): i32</code></pre>"), "{}", html);
+    assert!(html.contains("<p>Doubles <code>n</code>, see also <a href=\"#answer\"><code>answer</code></a></p>"), "{}", html);
+    assert!(html.contains("<pre><code>return twice(answer)</code></pre>"), "{}", html);
+    assert!(html.contains("<p>Adds &lt;a&gt; &amp; &lt;b&gt;</p>"), "{}", html);
+    assert!(html.contains("<p class=\"calls\">Calls <a href=\"#add\"><code>add</code></a>.</p>"), "{}", html);
+}
+
+#[test]
+fn command() {
+    let dir = env::temp_dir().join(format!("elgin-doc-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin"))
+        .args(["doc", "examples/factorial.eln", "-o"])
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let html = fs::read_to_string(dir.join("index.html")).unwrap();
+    let markdown = fs::read_to_string(dir.join("index.md")).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    assert!(html.contains("<title>factorial</title>"));
+    assert!(html.contains("<p>Simple factorial function\nAlso a great example of recursion</p>"), "{}", html);
+    assert!(markdown.contains("```\nproc factorial(n: i32): i32\n```\n"), "{}", markdown);
+    // main has no docs
+    assert!(!markdown.contains("main"));
+}
+
+#[test]
+fn doc_tests() {
+    let source = "\
+#: ```
+#: return 0
+#: ```
+#:
+#: ```no_run
+#: proc main(): i32 {
+#:     return one()
+#: }
+#: ```
+proc one(): i32 {
+    return 1
+}
+
+#: ```ignore
+#: not elgin
+#: ```
+#:
+#: ```text
+#: not a test
+#: ```
+const two: i32 = 2
+
+proc main(): i32 {
+    return one()
+}
+";
+    let tests = tests(source);
+    let names: Vec<_> = tests.iter().map(|t| (t.name.as_str(), t.mode)).collect();
+    assert_eq!(
+        names,
+        [
+            ("one (line 1)", DocTestMode::Run),
+            ("one (line 5)", DocTestMode::NoRun),
+            ("two (line 14)", DocTestMode::Ignore),
+        ]
+    );
+    let prelude = "proc one(): i32 {\n    return 1\n}\n\nconst two: i32 = 2\n\n";
+    assert_eq!(tests[0].source, format!("{}proc main(): i32 {{\n    return 0\n    return 0\n}}\n", prelude));
+    assert_eq!(tests[1].source, format!("{}proc main(): i32 {{\n    return one()\n}}\n", prelude));
+}
+
+#[test]
+fn test_command() {
+    let stdout = elgin_test("fail", SOURCE, 1);
+    assert!(stdout.contains("\nrunning 1 doc-test\ntest test.eln - twice (line 6) ... FAILED\n"), "{}", stdout);
+    assert!(stdout.contains("---- test.eln - twice (line 6) ----\nthe test exited with status 42\n"), "{}", stdout);
+    assert!(stdout.ends_with("\ntest result: FAILED. 0 passed; 1 failed; 0 ignored\n\n"), "{}", stdout);
+
+    let source = SOURCE.replace("#: return twice(answer)", "#: if twice(answer) != 42 {\n#:     return 1\n#: }");
+    let stdout = elgin_test("pass", &source, 0);
+    assert!(stdout.contains("test test.eln - twice (line 6) ... ok\n"), "{}", stdout);
+    assert!(stdout.ends_with("\ntest result: ok. 1 passed; 0 failed; 0 ignored\n\n"), "{}", stdout);
+
+    let source = SOURCE.replace("#: return twice(answer)", "#: return twice(missing)");
+    let stdout = elgin_test("error", &source, 1);
+    assert!(stdout.contains("name error[E0009]"), "{}", stdout);
+}
@@ -0,0 +1,495 @@
+//! Malformed source has to be reported, never crash the front end
+
+use std::fs;
+
+use elgin::astgen::Node;
+use elgin::cst::{self, Element, SyntaxKind, TriviaKind};
+use elgin::errors::{Diagnostics, Span};
+use elgin::ir::InstructionType;
+use elgin::lexer::{Lexer, Token};
+use elgin::parser::Parser;
+use elgin::source_map::SourceMap;
+use elgin::types::Type;
+
+/// Runs the front end over `source`, returning the codes of the errors it logged
+fn check(source: &str) -> Vec<&'static str> {
+    let mut sources = SourceMap::new();
+    let file = sources.add("test.eln", source.to_owned());
+    let diagnostics = Diagnostics::new();
+    let procs = elgin::check(&sources, file, &diagnostics);
+    assert_eq!(procs.is_none(), diagnostics.has_errors(), "{}", source);
+    diagnostics.into_errors().iter().filter_map(|e| e.code).collect()
+}
+
+#[test]
+fn malformed_input() {
+    let cases = [
+        ("", "E0006"),
+        (".", "E0012"),
+        ("proc main(): i32 {\n    return 1 +", "E0006"),
+        ("proc main(): i32 {\n    return 1 #", "E0002"),
+        ("proc main(): i32 {\n    return 1 € 2\n}\n", "E0011"),
+        ("proc main(): i32 {\n    return )\n}\n", "E0012"),
+        ("proc main(): i32 {\n    return %1\n}\n", "E0012"),
+        ("proc main(): i32 {\n    proc f(): i32 {\n        return 1\n    }\n}\n", "E0013"),
+        ("proc main(): i32 {\n    var b = 1 == 1\n    return !b\n}\n", "E0014"),
+        ("proc main(): i32 {\n    return f(1)\n}\nproc f(a: i32, b: i32): i32 {\n    return a\n}\n", "E0015"),
+        ("proc main(): i32 {\n    var a: i32 = 1\n    return a[0]\n}\n", "E0016"),
+        ("proc main() {\n    var a: [99999999999999999999999]i32\n}\n", "E0005"),
+        ("proc main() {\n    puts(\"\\q\")\n}\n", "E0017"),
+        ("proc main() {\n    puts(\"\\xFF \\u{D800}\")\n}\n", "E0017"),
+        ("proc main() {\n    var c = 'ab'\n}\n", "E0018"),
+        ("proc main() {\n    var c = 'é'\n}\n", "E0018"),
+        ("proc main() {\n    var c = '\n}\n", "E0018"),
+        ("proc main() {\n    puts(r#\"\")\n}\n", "E0001"),
+        ("proc main(): i32 {\n    return 0x\n}\n", "E0019"),
+        ("proc main(): i32 {\n    return 0b102\n}\n", "E0019"),
+        ("proc main(): i32 {\n    return 1e\n}\n", "E0019"),
+        ("proc main(): i32 {\n    return 1q32\n}\n", "E0019"),
+        ("proc main(): i32 {\n    return 1.5i32\n}\n", "E0019"),
+        ("proc main(): n8 {\n    return 256n8\n}\n", "E0019"),
+        ("proc main(): i32 {\n    return 1 && 2\n}\n", "E0020"),
+        ("proc main(): i32 {\n    return 1 ~ 2\n}\n", "E0020"),
+        ("#[ #[ nested ]#\nproc main() {\n}\n", "E0021"),
+        ("proc main() {\n    break\n}\n", "E0022"),
+        ("proc main() {\n    continue\n}\n", "E0022"),
+        ("proc main() {\n    var x\n}\n", "E0023"),
+        ("proc f() {\n    var y: i32 = 1\n}\nproc main(): i32 {\n    var r: i32 = f()\n    return r\n}\n", "E0024"),
+        ("proc f() {\n    var y: i32 = 1\n}\nproc main(): i32 {\n    return f()\n}\n", "E0024"),
+        ("proc f() {\n    return 1\n}\nproc main(): i32 {\n    return 0\n}\n", "E0024"),
+        ("proc main(): i32 {\n    return\n}\n", "E0024"),
+        ("proc main(): i32 {\n    var n: i32 = 1\n    if n {\n        return 1\n    }\n    return 0\n}\n", "E0025"),
+        ("proc main(): i32 {\n    var x: i64 = 1\n    return x\n}\n", "E0025"),
+        ("proc main(): i32 {\n    var b: bool = 1\n    return 0\n}\n", "E0025"),
+        ("proc main(): i32 {\n    return 4 / 2\n}\n", "E0025"),
+        ("proc main(): i32 {\n    var x: i32 = 1\n}\n", "E0026"),
+        ("proc main(): i32 {\n    if 1 == 1 {\n        return 1\n    }\n}\n", "E0026"),
+        ("proc main(): i32 {\n    while 1 == 1 {\n        return 1\n    }\n}\n", "E0026"),
+        ("const a: i32 = a\nproc main(): i32 {\n    return a\n}\n", "E0027"),
+        ("const a: i32 = b + 1\nconst b: i32 = a\nproc main(): i32 {\n    return b\n}\n", "E0027"),
+    ];
+    for (source, code) in &cases {
+        assert!(check(source).contains(code), "{:?} should give {}", source, code);
+    }
+
+    // deep nesting is reported before it can overflow the parser's stack
+    let parens = format!("proc main(): i32 {{\n    return {}1{}\n}}\n", "(".repeat(100_000), ")".repeat(100_000));
+    let blocks = format!("proc main(): i32 {{\n{}return 1\n{}}}\n", "if 1 == 1 {\n".repeat(100_000), "}\n".repeat(100_000));
+    for source in [parens, blocks] {
+        assert!(check(&source).contains(&"E0028"));
+    }
+}
+
+#[test]
+fn loops() {
+    let source = "\
+proc main(): i32 {
+    var i: i32 = 0
+    loop {
+        while i < 10 {
+            i = i + 1
+            continue
+        }
+        break
+    }
+    return i
+}
+";
+    assert_eq!(check(source), Vec::<&str>::new());
+    // the loop has ended by the time of the break
+    let source = "proc main() {\n    while 1 < 2 {\n        continue\n    }\n    break\n}\n";
+    assert_eq!(check(source), ["E0022"]);
+}
+
+#[test]
+fn negation() {
+    assert!(check("proc main(): i32 {\n    return -1\n}\n").is_empty());
+}
+
+#[test]
+fn literal_defaults() {
+    // a variable holding only literals gets their default type
+    let source = "proc main(): i32 {\n    var f = 1.5\n    var g = f * 2.0\n    var x = 1\n    return x\n}\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("test.eln", source.to_owned());
+    let diagnostics = Diagnostics::new();
+    let procs = elgin::check(&sources, file, &diagnostics).unwrap();
+    let main = procs.iter().find(|p| p.name == "main").unwrap();
+    let vars: Vec<_> = main
+        .body
+        .iter()
+        .filter_map(|ins| match &ins.contents.ins {
+            InstructionType::Allocate(var) => Some((var.as_str(), ins.contents.typ.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vars, [("f", Type::F64), ("g", Type::F64), ("x", Type::I32)]);
+}
+
+#[test]
+fn operators() {
+    let ops = |source: &str| {
+        let diagnostics = Diagnostics::new();
+        let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+        tokens
+            .into_iter()
+            .filter_map(|t| match t.contents {
+                Token::Op(op) => Some(op),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ops("a*-b"), ["*", "-"]);
+    assert_eq!(ops("x!=-1"), ["!=", "-"]);
+    assert_eq!(ops("a+~b*~c-~-d"), ["+~", "*~", "-~", "-"]);
+    assert_eq!(ops("!(a<=b)==c//-d"), ["!", "<=", "==", "//", "-"]);
+    assert!(check("proc main(): i32 {\n    var a: i32 = 2\n    return a*-a +~ a*~-3 -~ -a\n}\n").is_empty());
+}
+
+/// Cuts up and splices together the examples, as a cheap stand-in for the fuzz target
+#[test]
+fn mutated_examples() {
+    let mut examples = vec![];
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "eln") {
+            examples.push(fs::read_to_string(path).unwrap().chars().collect::<Vec<_>>());
+        }
+    }
+    let fragments = [
+        "proc", "var", "if", "else", "while", "return", "(", ")", "[", "]", "{", "}", ",", "=", ":", "\n", "!",
+        "-", "*", ".", "#", "#:", "\"", "'", "\\", "r#\"", "é", "x", "1", "1.5", "[10]i32", "99999999999999999999999", "0x", "1e", "_", "n8", "*~", "&&", "#[", "]#",
+    ];
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut random = |n: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % n as u64) as usize
+    };
+    for _ in 0..2000 {
+        let mut source = examples[random(examples.len())].clone();
+        for _ in 0..1 + random(4) {
+            let pos = random(source.len() + 1);
+            match random(3) {
+                0 => {
+                    let fragment = fragments[random(fragments.len())];
+                    source.splice(pos..pos, fragment.chars());
+                }
+                1 => {
+                    let end = (pos + random(8)).min(source.len());
+                    source.drain(pos..end);
+                }
+                _ => source.truncate(pos),
+            }
+        }
+        check(&source.iter().collect::<String>());
+    }
+}
+
+#[test]
+fn unicode() {
+    let source = "proc main(): i32 {\n    var größe = 1\n    var _π = \"naïve 🦀\"\n    return größe\n}\n";
+    assert!(check(source).is_empty());
+
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+    let spans: Vec<_> = tokens.iter().map(|t| (&t.contents, &source[t.pos..t.pos + t.len])).collect();
+    assert!(spans.contains(&(&Token::Ident("größe".to_owned()), "größe")));
+    assert!(spans.contains(&(&Token::Ident("_π".to_owned()), "_π")));
+    assert!(spans.contains(&(&Token::StrLiteral("naïve 🦀".to_owned()), "\"naïve 🦀\"")));
+}
+
+#[test]
+fn literals() {
+    let source = r####"puts("a\"b\tc\\\0\x41\u{e9}\u{1F980}\n") 'a' '\'' '\n' r"C:\n" r#"say "hi""# r##""#"##"####;
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+    assert!(!diagnostics.has_errors());
+    let literals: Vec<_> = tokens
+        .iter()
+        .map(|t| &t.contents)
+        .filter(|t| matches!(t, Token::StrLiteral(_) | Token::CharLiteral(_)))
+        .collect();
+    assert_eq!(
+        literals,
+        [
+            &Token::StrLiteral("a\"b\tc\\\0A\u{e9}\u{1F980}\n".to_owned()),
+            &Token::CharLiteral('a'),
+            &Token::CharLiteral('\''),
+            &Token::CharLiteral('\n'),
+            &Token::StrLiteral("C:\\n".to_owned()),
+            &Token::StrLiteral("say \"hi\"".to_owned()),
+            &Token::StrLiteral("\"#".to_owned()),
+        ]
+    );
+    assert!(check("proc main(): n8 {\n    var c = 'a'\n    return c + '\\x01'\n}\n").is_empty());
+    // write takes the escaped newline as it is, unlike puts which adds its own
+    assert!(check("proc main(): i32 {\n    write(1, \"hi\\n\", 3n64)\n    return 0\n}\n").is_empty());
+}
+
+#[test]
+fn numbers() {
+    let source = "1_000 0xFF_ff 0o17 0b1010 255n8 127i8 .5 1. 1.5e-9 2E+3 1e3f32 1.5f32 7f64";
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+    assert!(!diagnostics.has_errors());
+    let int = |s: &str, t| Token::IntLiteral(s.to_owned(), t);
+    let float = |s: &str, t| Token::FloatLiteral(s.to_owned(), t);
+    assert_eq!(
+        tokens.into_iter().map(|t| t.contents).collect::<Vec<_>>(),
+        [
+            int("1000", None),
+            int("65535", None),
+            int("15", None),
+            int("10", None),
+            int("255", Some(Type::N8)),
+            int("127", Some(Type::I8)),
+            float(".5", None),
+            float("1.", None),
+            float("1.5e-9", None),
+            float("2E+3", None),
+            float("1e3", Some(Type::F32)),
+            float("1.5", Some(Type::F32)),
+            float("7", Some(Type::F64)),
+        ]
+    );
+
+    // a signed type's minimum can only be written negated
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new("-128i8 (-~32768i16)", Default::default(), &diagnostics).go().unwrap();
+    assert!(!diagnostics.has_errors());
+    assert_eq!(tokens[1].contents, int("128", Some(Type::I8)));
+    assert_eq!(tokens[4].contents, int("32768", Some(Type::I16)));
+    for source in ["128i8", "1 -128i8", "x -128i8", "-129i8"] {
+        let diagnostics = Diagnostics::new();
+        Lexer::new(source, Default::default(), &diagnostics).go();
+        let codes: Vec<_> = diagnostics.into_errors().iter().filter_map(|e| e.code).collect();
+        assert_eq!(codes, ["E0019"], "{}", source);
+    }
+    assert!(check("proc main(): i32 {\n    var x: i8 = -128i8\n    return 0\n}\n").is_empty());
+}
+
+#[test]
+fn comments() {
+    let source = "\
+#[ a block comment #[ which nests ]#
+   and carries on ]#
+#: Answers
+#:
+#:  everything
+proc answer(): i32 {
+    return 42 # a comment doesn't hide the line break
+}
+
+#: The answer, #[ not a block comment
+const a: i32 = 42
+#[]#
+proc main(): i32 {
+    #: documents nothing
+    return answer() #[ inline ]# + a
+}
+";
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+    let ast = Parser::new(&tokens, Default::default(), &diagnostics).go().unwrap();
+    let docs: Vec<_> = ast
+        .iter()
+        .map(|node| match &node.contents {
+            Node::ProcStatement { docs, .. } | Node::ConstStatement { docs, .. } => docs.as_str(),
+            n => panic!("{:?}", n),
+        })
+        .collect();
+    assert_eq!(docs, ["Answers\n\n everything", "The answer, #[ not a block comment", ""]);
+
+    let errors = diagnostics.into_errors();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].lint, Some("unused_doc_comments"));
+    assert_eq!(&source[errors[0].pos..errors[0].pos + errors[0].len], "#: documents nothing");
+}
+
+fn check_spans(node: &Span<Node>, parent: (usize, usize), source: &str) {
+    let (pos, len) = (node.pos, node.len);
+    assert!(pos >= parent.0 && pos + len <= parent.1, "{:?} is outside of its parent", node);
+    // only the undefined values filled in for missing code are empty
+    if let Node::Literal { typ: Type::Undefined, .. } = node.contents {
+        assert_eq!(len, 0, "{:?}", node);
+    } else if len == 0 {
+        assert!(matches!(&node.contents, Node::Block { nodes } if nodes.len() <= 1), "{:?} is empty", node);
+    } else {
+        let text = &source[pos..pos + len];
+        assert!(!text.starts_with(char::is_whitespace) && !text.ends_with(char::is_whitespace), "{:?}", node);
+    }
+    for child in node.contents.children() {
+        check_spans(child, (pos, pos + len), source);
+    }
+}
+
+#[test]
+fn spans() {
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "eln") {
+            continue;
+        }
+        let mut sources = SourceMap::new();
+        let file = sources.load(path.to_str().unwrap()).unwrap();
+        let source = sources.get(file).source();
+        let diagnostics = Diagnostics::new();
+        let tokens = Lexer::new(source, file, &diagnostics).go().unwrap();
+        let ast = Parser::new(&tokens, file, &diagnostics).go().unwrap();
+        for node in &ast {
+            check_spans(node, (0, source.len()), source);
+        }
+
+        for proc in elgin::check(&sources, file, &diagnostics).unwrap() {
+            for ins in &proc.body {
+                assert!(ins.pos + ins.len <= source.len(), "{:?} in {:?}", ins, path);
+                assert!(ins.len > 0 || ins.contents.typ == Type::Undefined, "{:?} in {:?}", ins, path);
+            }
+        }
+    }
+}
+
+#[test]
+fn error_nodes() {
+    let source = "proc main(): i32 {\n    var x = )\n    return 0\n}\nconst c: int = 1\n";
+    let diagnostics = Diagnostics::new();
+    let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+    let ast = Parser::new(&tokens, Default::default(), &diagnostics).go().unwrap();
+    assert_eq!(diagnostics.into_errors().len(), 2);
+    assert!(matches!(ast[1].contents, Node::Error));
+    match &ast[0].contents {
+        Node::ProcStatement { body, .. } => match &body.contents {
+            Node::Block { nodes } => {
+                assert!(matches!(nodes[0].contents, Node::Error));
+                assert!(matches!(nodes[1].contents, Node::ReturnStatement { .. }));
+            }
+            n => panic!("{:?}", n),
+        },
+        n => panic!("{:?}", n),
+    }
+}
+
+#[test]
+fn source_map() {
+    let mut sources = SourceMap::new();
+    let first = sources.add("first.eln", "proc main() {\n".to_owned());
+    let file = sources.add("café.eln", "var café = 1\n\n  x = \"é\"\n".to_owned());
+    assert_ne!(first, file);
+    let file = sources.get(file);
+    assert_eq!(file.name(), "café.eln");
+
+    // `é` is two bytes, so the columns after it are behind the byte offsets
+    assert_eq!(file.line_col(0), (1, 1));
+    assert_eq!(file.line_col(8), (1, 8));
+    assert_eq!(file.line_col(9), (1, 9));
+    assert_eq!(file.line_col(14), (2, 1));
+    assert_eq!(file.line_col(17), (3, 3));
+    assert_eq!(file.line_col(file.source().len()), (4, 1));
+    assert_eq!(file.offset(1, 9), 9);
+    assert_eq!(file.offset(3, 3), 17);
+    assert_eq!(file.offset(2, 40), 14);
+
+    assert_eq!(file.line(1), "var café = 1");
+    assert_eq!(file.line(2), "");
+    assert_eq!(file.line(4), "");
+    assert_eq!(file.char_to_byte(8), 9);
+    assert_eq!(file.char_to_byte(99), file.source().len());
+    for (index, (offset, _)) in file.source().char_indices().enumerate() {
+        assert_eq!(file.char_to_byte(index), offset);
+        assert_eq!(file.byte_to_char(offset), index);
+    }
+}
+
+#[test]
+fn lossless() {
+    let mut sources: Vec<String> = fs::read_dir("examples")
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    sources.extend(
+        [
+            "",
+            "  \n\n",
+            "proc main(): i32 {  # trailing\n\n\n    return 1 #[ a #[ nested ]# one ]#+ 2\n}",
+            "#: docs\n\n#: more\nconst c: i32 = 1\r\n",
+            "proc main(): i32 {\n    var x = )\n    return 0 €\n}\n#[ unterminated",
+            "proc f(a: i32,\n       b: i32) {\n\tputs(\"{\")\n}",
+        ]
+        .iter()
+        .map(|s| s.to_string()),
+    );
+    for source in &sources {
+        let diagnostics = Diagnostics::new();
+        let tree = cst::parse(source, Default::default(), &diagnostics);
+        assert_eq!(tree.to_string(), *source);
+
+        let tokens = Lexer::new(source, Default::default(), &diagnostics).go().unwrap();
+        let ast = Parser::new(&tokens, Default::default(), &diagnostics).go();
+        let derived = tree.to_ast(Default::default(), &diagnostics);
+        assert_eq!(format!("{:?}", derived), format!("{:?}", ast), "{}", source);
+    }
+}
+
+#[test]
+fn trivia() {
+    let source = "proc main(): i32 { # hi\n\n    return 1 #[ x ]#\n}\n\n";
+    let diagnostics = Diagnostics::new();
+    let tokens = cst::lex(source, Default::default(), &diagnostics);
+    let leading: Vec<_> = tokens
+        .iter()
+        .filter(|t| !t.leading.is_empty())
+        .map(|t| (t.text.as_str(), t.leading.iter().map(|l| (l.kind, l.text.as_str())).collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        leading,
+        [
+            ("main", vec![(TriviaKind::Whitespace, " ")]),
+            ("i32", vec![(TriviaKind::Whitespace, " ")]),
+            ("{", vec![(TriviaKind::Whitespace, " ")]),
+            ("\n", vec![(TriviaKind::Whitespace, " "), (TriviaKind::Comment, "# hi")]),
+            ("return", vec![(TriviaKind::Whitespace, "\n    ")]),
+            ("1", vec![(TriviaKind::Whitespace, " ")]),
+            ("\n", vec![(TriviaKind::Whitespace, " "), (TriviaKind::BlockComment, "#[ x ]#")]),
+            ("", vec![(TriviaKind::Whitespace, "\n")]),
+        ]
+    );
+    assert_eq!(tokens.last().unwrap().token.contents, Token::EOF);
+
+    let tree = cst::parse(source, Default::default(), &diagnostics);
+    let proc = match &tree.children[0] {
+        Element::Node(node) => node,
+        e => panic!("{:?}", e),
+    };
+    assert_eq!(proc.kind, SyntaxKind::ProcStatement);
+    let kinds: Vec<_> = proc
+        .children
+        .iter()
+        .map(|c| match c {
+            Element::Node(node) => format!("{:?}", node.kind),
+            Element::Token(token) => token.text.clone(),
+        })
+        .collect();
+    assert_eq!(kinds, ["proc", "main", "(", ")", ":", "i32", "Block"]);
+    // the newline after the proc is outside of it, along with the EOF
+    assert_eq!(tree.children.len(), 3);
+}
+
+#[test]
+fn unterminated_string() {
+    for source in ["proc main() {\n    puts(\"hi)\n}\n", "proc main() {\n    puts(r#\"hi\"\n}\n"] {
+        let diagnostics = Diagnostics::new();
+        let tree = cst::parse(source, Default::default(), &diagnostics);
+        assert_eq!(tree.to_string(), source);
+        assert!(diagnostics.has_errors());
+
+        let tokens = tree.tokens();
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.token.contents, Token::EOF);
+        let skipped = eof.leading.last().unwrap();
+        assert_eq!(skipped.kind, TriviaKind::Skipped);
+        assert_eq!(skipped.pos, source.find("puts(").unwrap() + 5);
+        assert_eq!(skipped.text, source[skipped.pos..]);
+    }
+}
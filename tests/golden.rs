@@ -0,0 +1,102 @@
+//! Golden tests for the LLVM IR generated from `examples/` at each optimization level
+//!
+//! Set `ELGIN_BLESS=1` to rewrite the expected output in `tests/golden/` after an intended change.
+//!
+//! The optimizer's output changes between LLVM releases, so the goldens are only compared when
+//! the LLVM being linked against has the major version in `tests/golden/LLVM_VERSION`, which is
+//! the one they were blessed with.
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+const EXAMPLES: [&str; 5] = ["factorial", "fibonacci", "hello", "quicksort", "test"];
+
+const VERSION_FILE: &str = "tests/golden/LLVM_VERSION";
+
+fn check_level(level: &str) {
+    let bless = env::var_os("ELGIN_BLESS").is_some();
+    let version = llvm_major_version();
+    if bless {
+        fs::write(VERSION_FILE, format!("{}\n", version)).unwrap();
+    } else {
+        let blessed = fs::read_to_string(VERSION_FILE).unwrap();
+        if blessed.trim() != version {
+            eprintln!(
+                "skipping the -O{} goldens, which were blessed with LLVM {} rather than LLVM {}",
+                level,
+                blessed.trim(),
+                version,
+            );
+            return;
+        }
+    }
+    let mut mismatches = vec![];
+    for example in &EXAMPLES {
+        let output = Command::new(env!("CARGO_BIN_EXE_elgin"))
+            .args(["emit", "llvm-ir", &format!("-O{}", level)])
+            .arg(format!("examples/{}.eln", example))
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "compiling {} at -O{} failed:\n{}",
+            example,
+            level,
+            String::from_utf8_lossy(&output.stderr),
+        );
+        let actual = normalize(&String::from_utf8(output.stdout).unwrap());
+
+        let golden = format!("tests/golden/{}.O{}.ll", example, level);
+        if bless {
+            fs::write(&golden, &actual).unwrap();
+        } else if fs::read_to_string(&golden).map(|s| normalize(&s)).ok() != Some(actual) {
+            mismatches.push(golden);
+        }
+    }
+    assert!(mismatches.is_empty(), "output differs from {:?}", mismatches);
+}
+
+/// The major version of the LLVM which llvm-sys links against, found the same way it does
+fn llvm_major_version() -> String {
+    let llvm_config = match env::var_os("LLVM_SYS_100_PREFIX") {
+        Some(prefix) => std::path::Path::new(&prefix).join("bin/llvm-config"),
+        None => "llvm-config".into(),
+    };
+    let output = Command::new(&llvm_config).arg("--version").output().unwrap();
+    let version = String::from_utf8(output.stdout).unwrap();
+    version.trim().split('.').next().unwrap().to_owned()
+}
+
+/// Drops the host specific target lines so the goldens hold on any machine
+fn normalize(ir: &str) -> String {
+    ir.lines()
+        .filter(|l| !l.starts_with("target datalayout") && !l.starts_with("target triple"))
+        .map(|l| format!("{}\n", l))
+        .collect()
+}
+
+#[test]
+fn golden_o0() {
+    check_level("0");
+}
+
+#[test]
+fn golden_o1() {
+    check_level("1");
+}
+
+#[test]
+fn golden_o2() {
+    check_level("2");
+}
+
+#[test]
+fn golden_o3() {
+    check_level("3");
+}
+
+#[test]
+fn golden_os() {
+    check_level("s");
+}
@@ -0,0 +1,35 @@
+; ModuleID = 'elgin'
+source_filename = "examples/factorial.eln"
+
+declare i32 @puts(i8*)
+
+declare i64 @write(i32, i8*, i64)
+
+define i32 @factorial(i32 %0) {
+entry:
+  %n = alloca i32, align 4
+  store i32 %0, i32* %n, align 4
+  %tmpload = load i32, i32* %n, align 4
+  %tmpcmp = icmp eq i32 %tmpload, 0
+  br i1 %tmpcmp, label %lbl0, label %lbl1
+
+lbl0:                                             ; preds = %entry
+  ret i32 1
+
+lbl1:                                             ; preds = %entry
+  %tmpload1 = load i32, i32* %n, align 4
+  %tmpload2 = load i32, i32* %n, align 4
+  %tmpsub = sub nsw i32 %tmpload2, 1
+  %tmpcall = call i32 @factorial(i32 %tmpsub)
+  %tmpmul = mul nsw i32 %tmpcall, %tmpload1
+  ret i32 %tmpmul
+}
+
+define i32 @main() {
+entry:
+  %tmpcall = call i32 @factorial(i32 5)
+  %res = alloca i32, align 4
+  store i32 %tmpcall, i32* %res, align 4
+  %tmpload = load i32, i32* %res, align 4
+  ret i32 %tmpload
+}
@@ -0,0 +1,28 @@
+; ModuleID = 'elgin'
+source_filename = "examples/factorial.eln"
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @factorial(i32 %0) local_unnamed_addr #0 {
+entry:
+  %tmpcmp = icmp eq i32 %0, 0
+  br i1 %tmpcmp, label %common.ret, label %lbl1
+
+common.ret:                                       ; preds = %entry, %lbl1
+  %common.ret.op = phi i32 [ %tmpmul, %lbl1 ], [ 1, %entry ]
+  ret i32 %common.ret.op
+
+lbl1:                                             ; preds = %entry
+  %tmpsub = add nsw i32 %0, -1
+  %tmpcall = call i32 @factorial(i32 %tmpsub)
+  %tmpmul = mul nsw i32 %tmpcall, %0
+  br label %common.ret
+}
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @main() local_unnamed_addr #0 {
+entry:
+  %tmpcall = call i32 @factorial(i32 5)
+  ret i32 %tmpcall
+}
+
+attributes #0 = { nofree nosync nounwind readnone }
@@ -0,0 +1,30 @@
+; ModuleID = 'elgin'
+source_filename = "examples/factorial.eln"
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @factorial(i32 %0) local_unnamed_addr #0 {
+entry:
+  %tmpcmp5 = icmp eq i32 %0, 0
+  br i1 %tmpcmp5, label %common.ret, label %lbl1
+
+common.ret:                                       ; preds = %lbl1, %entry
+  %accumulator.tr.lcssa = phi i32 [ 1, %entry ], [ %tmpmul, %lbl1 ]
+  ret i32 %accumulator.tr.lcssa
+
+lbl1:                                             ; preds = %entry, %lbl1
+  %.tr7 = phi i32 [ %tmpsub, %lbl1 ], [ %0, %entry ]
+  %accumulator.tr6 = phi i32 [ %tmpmul, %lbl1 ], [ 1, %entry ]
+  %tmpsub = add nsw i32 %.tr7, -1
+  %tmpmul = mul nsw i32 %.tr7, %accumulator.tr6
+  %tmpcmp = icmp eq i32 %tmpsub, 0
+  br i1 %tmpcmp, label %common.ret, label %lbl1
+}
+
+; Function Attrs: nofree norecurse nosync nounwind readnone
+define i32 @main() local_unnamed_addr #1 {
+entry:
+  ret i32 120
+}
+
+attributes #0 = { nofree nosync nounwind readnone }
+attributes #1 = { nofree norecurse nosync nounwind readnone }
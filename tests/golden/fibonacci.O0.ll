@@ -0,0 +1,34 @@
+; ModuleID = 'elgin'
+source_filename = "examples/fibonacci.eln"
+
+declare i32 @puts(i8*)
+
+declare i64 @write(i32, i8*, i64)
+
+define i32 @main() {
+entry:
+  %tmpcall = call i32 @fibonnaci(i32 10)
+  ret i32 %tmpcall
+}
+
+define i32 @fibonnaci(i32 %0) {
+entry:
+  %n = alloca i32, align 4
+  store i32 %0, i32* %n, align 4
+  %tmpload = load i32, i32* %n, align 4
+  %tmpcmp = icmp slt i32 %tmpload, 2
+  br i1 %tmpcmp, label %lbl0, label %lbl1
+
+lbl0:                                             ; preds = %entry
+  ret i32 1
+
+lbl1:                                             ; preds = %entry
+  %tmpload1 = load i32, i32* %n, align 4
+  %tmpsub = sub nsw i32 %tmpload1, 1
+  %tmpcall = call i32 @fibonnaci(i32 %tmpsub)
+  %tmpload2 = load i32, i32* %n, align 4
+  %tmpsub3 = sub nsw i32 %tmpload2, 2
+  %tmpcall4 = call i32 @fibonnaci(i32 %tmpsub3)
+  %tmpadd = add nsw i32 %tmpcall4, %tmpcall
+  ret i32 %tmpadd
+}
@@ -0,0 +1,30 @@
+; ModuleID = 'elgin'
+source_filename = "examples/fibonacci.eln"
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @main() local_unnamed_addr #0 {
+entry:
+  %tmpcall = call i32 @fibonnaci(i32 10)
+  ret i32 %tmpcall
+}
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @fibonnaci(i32 %0) local_unnamed_addr #0 {
+entry:
+  %tmpcmp = icmp slt i32 %0, 2
+  br i1 %tmpcmp, label %common.ret, label %lbl1
+
+common.ret:                                       ; preds = %entry, %lbl1
+  %common.ret.op = phi i32 [ %tmpadd, %lbl1 ], [ 1, %entry ]
+  ret i32 %common.ret.op
+
+lbl1:                                             ; preds = %entry
+  %tmpsub = add nsw i32 %0, -1
+  %tmpcall = call i32 @fibonnaci(i32 %tmpsub)
+  %tmpsub3 = add nsw i32 %0, -2
+  %tmpcall4 = call i32 @fibonnaci(i32 %tmpsub3)
+  %tmpadd = add nsw i32 %tmpcall4, %tmpcall
+  br label %common.ret
+}
+
+attributes #0 = { nofree nosync nounwind readnone }
@@ -0,0 +1,36 @@
+; ModuleID = 'elgin'
+source_filename = "examples/fibonacci.eln"
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @main() local_unnamed_addr #0 {
+entry:
+  %tmpcall = tail call i32 @fibonnaci(i32 10)
+  ret i32 %tmpcall
+}
+
+; Function Attrs: nofree nosync nounwind readnone
+define i32 @fibonnaci(i32 %0) local_unnamed_addr #0 {
+entry:
+  %tmpcmp7 = icmp slt i32 %0, 2
+  br i1 %tmpcmp7, label %common.ret, label %lbl1
+
+common.ret.loopexit:                              ; preds = %lbl1
+  %phi.bo = add i32 %tmpadd, 1
+  br label %common.ret
+
+common.ret:                                       ; preds = %common.ret.loopexit, %entry
+  %accumulator.tr.lcssa = phi i32 [ 1, %entry ], [ %phi.bo, %common.ret.loopexit ]
+  ret i32 %accumulator.tr.lcssa
+
+lbl1:                                             ; preds = %entry, %lbl1
+  %.tr9 = phi i32 [ %tmpsub3, %lbl1 ], [ %0, %entry ]
+  %accumulator.tr8 = phi i32 [ %tmpadd, %lbl1 ], [ 0, %entry ]
+  %tmpsub = add nsw i32 %.tr9, -1
+  %tmpcall = tail call i32 @fibonnaci(i32 %tmpsub)
+  %tmpsub3 = add nsw i32 %.tr9, -2
+  %tmpadd = add nsw i32 %tmpcall, %accumulator.tr8
+  %tmpcmp = icmp ult i32 %.tr9, 4
+  br i1 %tmpcmp, label %common.ret.loopexit, label %lbl1
+}
+
+attributes #0 = { nofree nosync nounwind readnone }
@@ -0,0 +1,14 @@
+; ModuleID = 'elgin'
+source_filename = "examples/hello.eln"
+
+@tmpstr = private unnamed_addr constant [14 x i8] c"Hello world!\0A\00", align 1
+
+declare i32 @puts(i8*)
+
+declare i64 @write(i32, i8*, i64)
+
+define i32 @main() {
+entry:
+  %tmpcall = call i64 @write(i32 1, i8* getelementptr inbounds ([14 x i8], [14 x i8]* @tmpstr, i32 0, i32 0), i64 13)
+  ret i32 0
+}
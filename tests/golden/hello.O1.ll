@@ -0,0 +1,16 @@
+; ModuleID = 'elgin'
+source_filename = "examples/hello.eln"
+
+@tmpstr = private unnamed_addr constant [14 x i8] c"Hello world!\0A\00", align 1
+
+; Function Attrs: nofree
+declare noundef i64 @write(i32 noundef, i8* nocapture noundef readonly, i64 noundef) local_unnamed_addr #0
+
+; Function Attrs: nofree
+define i32 @main() local_unnamed_addr #0 {
+entry:
+  %tmpcall = call i64 @write(i32 1, i8* getelementptr inbounds ([14 x i8], [14 x i8]* @tmpstr, i64 0, i64 0), i64 13)
+  ret i32 0
+}
+
+attributes #0 = { nofree }
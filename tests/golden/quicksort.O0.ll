@@ -1,16 +1,19 @@
-source_filename = "quicksort.eln"
+; ModuleID = 'elgin'
+source_filename = "examples/quicksort.eln"
 
 declare i32 @puts(i8*)
 
+declare i64 @write(i32, i8*, i64)
+
 define void @quicksort10([10 x i32] %0, i32 %1, i32 %2) {
 entry:
-  %a = alloca [10 x i32]
-  store [10 x i32] %0, [10 x i32]* %a
-  %start = alloca i32
-  store i32 %1, i32* %start
-  %len = alloca i32
-  store i32 %2, i32* %len
-  %tmpload = load i32, i32* %len
+  %a = alloca [10 x i32], align 4
+  store [10 x i32] %0, [10 x i32]* %a, align 4
+  %start = alloca i32, align 4
+  store i32 %1, i32* %start, align 4
+  %len = alloca i32, align 4
+  store i32 %2, i32* %len, align 4
+  %tmpload = load i32, i32* %len, align 4
   %tmpcmp = icmp slt i32 %tmpload, 2
   br i1 %tmpcmp, label %lbl0, label %lbl1
 
@@ -21,17 +24,17 @@ lbl1:                                             ; preds = %entry
   br label %lbl2
 
 lbl2:                                             ; preds = %lbl1
-  %tmpload1 = load [10 x i32], [10 x i32]* %a
-  %tmpload2 = load i32, i32* %start
+  %tmpload1 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload2 = load i32, i32* %start, align 4
   %tmpadd = add nsw i32 0, %tmpload2
   %tmpgep = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpadd
-  %tmpload3 = load i32, i32* %tmpgep
-  %pivot = alloca i32
-  store i32 %tmpload3, i32* %pivot
-  %i = alloca i32
-  store i32 0, i32* %i
-  %j = alloca i32
-  store i32 9, i32* %j
+  %tmpload3 = load i32, i32* %tmpgep, align 4
+  %pivot = alloca i32, align 4
+  store i32 %tmpload3, i32* %pivot, align 4
+  %i = alloca i32, align 4
+  store i32 0, i32* %i, align 4
+  %j = alloca i32, align 4
+  store i32 9, i32* %j, align 4
   br label %lbl3
 
 lbl3:                                             ; preds = %lbl14, %lbl2
@@ -41,94 +44,93 @@ lbl4:                                             ; preds = %lbl3
   br label %lbl6
 
 lbl6:                                             ; preds = %lbl7, %lbl4
-  %tmpload4 = load [10 x i32], [10 x i32]* %a
-  %tmpload5 = load i32, i32* %start
-  %tmpload6 = load i32, i32* %i
+  %tmpload4 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload5 = load i32, i32* %start, align 4
+  %tmpload6 = load i32, i32* %i, align 4
   %tmpadd7 = add nsw i32 %tmpload6, %tmpload5
   %tmpgep8 = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpadd7
-  %tmpload9 = load i32, i32* %tmpgep8
-  %tmpload10 = load i32, i32* %pivot
+  %tmpload9 = load i32, i32* %tmpgep8, align 4
+  %tmpload10 = load i32, i32* %pivot, align 4
   %tmpcmp11 = icmp slt i32 %tmpload9, %tmpload10
   br i1 %tmpcmp11, label %lbl7, label %lbl8
 
 lbl7:                                             ; preds = %lbl6
-  %tmpload12 = load i32, i32* %i
+  %tmpload12 = load i32, i32* %i, align 4
   %tmpadd13 = add nsw i32 1, %tmpload12
-  store i32 %tmpadd13, i32* %i
+  store i32 %tmpadd13, i32* %i, align 4
   br label %lbl6
 
 lbl8:                                             ; preds = %lbl6
   br label %lbl9
 
 lbl9:                                             ; preds = %lbl10, %lbl8
-  %tmpload14 = load [10 x i32], [10 x i32]* %a
-  %tmpload15 = load i32, i32* %start
-  %tmpload16 = load i32, i32* %j
+  %tmpload14 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload15 = load i32, i32* %start, align 4
+  %tmpload16 = load i32, i32* %j, align 4
   %tmpadd17 = add nsw i32 %tmpload16, %tmpload15
   %tmpgep18 = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpadd17
-  %tmpload19 = load i32, i32* %tmpgep18
-  %tmpload20 = load i32, i32* %pivot
+  %tmpload19 = load i32, i32* %tmpgep18, align 4
+  %tmpload20 = load i32, i32* %pivot, align 4
   %tmpcmp21 = icmp slt i32 %tmpload19, %tmpload20
   br i1 %tmpcmp21, label %lbl10, label %lbl11
 
 lbl10:                                            ; preds = %lbl9
-  %tmpload22 = load i32, i32* %j
+  %tmpload22 = load i32, i32* %j, align 4
   %tmpsub = sub nsw i32 %tmpload22, 1
-  store i32 %tmpsub, i32* %j
+  store i32 %tmpsub, i32* %j, align 4
   br label %lbl9
 
-lbl11:                                            ; preds = %lbl12, %lbl9
-  %tmpload23 = load i32, i32* %i
-  %tmpload24 = load i32, i32* %j
+lbl11:                                            ; preds = %lbl9
+  %tmpload23 = load i32, i32* %i, align 4
+  %tmpload24 = load i32, i32* %j, align 4
   %tmpcmp25 = icmp sge i32 %tmpload23, %tmpload24
   br i1 %tmpcmp25, label %lbl12, label %lbl13
 
 lbl12:                                            ; preds = %lbl11
-  br label %lbl11
-  br label %lbl14
+  br label %lbl5
 
 lbl13:                                            ; preds = %lbl11
   br label %lbl14
 
-lbl14:                                            ; preds = %lbl13, %lbl12
-  %tmpload26 = load [10 x i32], [10 x i32]* %a
-  %tmpload27 = load i32, i32* %start
-  %tmpload28 = load i32, i32* %i
+lbl14:                                            ; preds = %lbl13
+  %tmpload26 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload27 = load i32, i32* %start, align 4
+  %tmpload28 = load i32, i32* %i, align 4
   %tmpadd29 = add nsw i32 %tmpload28, %tmpload27
   %tmpgep30 = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpadd29
-  %tmpload31 = load i32, i32* %tmpgep30
-  %temp = alloca i32
-  store i32 %tmpload31, i32* %temp
-  %tmpload32 = load [10 x i32], [10 x i32]* %a
-  %tmpload33 = load i32, i32* %start
-  %tmpload34 = load i32, i32* %j
+  %tmpload31 = load i32, i32* %tmpgep30, align 4
+  %temp = alloca i32, align 4
+  store i32 %tmpload31, i32* %temp, align 4
+  %tmpload32 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload33 = load i32, i32* %start, align 4
+  %tmpload34 = load i32, i32* %j, align 4
   %tmpadd35 = add nsw i32 %tmpload34, %tmpload33
   %tmpgep36 = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpadd35
-  %tmpload37 = load i32, i32* %tmpgep36
-  %tmpload38 = load i32, i32* %start
-  %tmpload39 = load i32, i32* %i
+  %tmpload37 = load i32, i32* %tmpgep36, align 4
+  %tmpload38 = load i32, i32* %start, align 4
+  %tmpload39 = load i32, i32* %i, align 4
   %tmpadd40 = add nsw i32 %tmpload39, %tmpload38
   %tmpgep41 = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpload37
-  store i32 %tmpadd40, i32* %tmpgep41
-  %tmpload42 = load i32, i32* %temp
-  %tmpload43 = load i32, i32* %start
-  %tmpload44 = load i32, i32* %j
+  store i32 %tmpadd40, i32* %tmpgep41, align 4
+  %tmpload42 = load i32, i32* %temp, align 4
+  %tmpload43 = load i32, i32* %start, align 4
+  %tmpload44 = load i32, i32* %j, align 4
   %tmpadd45 = add nsw i32 %tmpload44, %tmpload43
   %tmpgep46 = getelementptr [10 x i32], [10 x i32]* %a, i32 0, i32 %tmpload42
-  store i32 %tmpadd45, i32* %tmpgep46
+  store i32 %tmpadd45, i32* %tmpgep46, align 4
   br label %lbl3
 
-lbl5:                                             ; preds = %lbl3
-  %tmpload47 = load [10 x i32], [10 x i32]* %a
-  %tmpload48 = load i32, i32* %start
-  %tmpload49 = load i32, i32* %i
+lbl5:                                             ; preds = %lbl12, %lbl3
+  %tmpload47 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload48 = load i32, i32* %start, align 4
+  %tmpload49 = load i32, i32* %i, align 4
   call void @quicksort10([10 x i32] %tmpload47, i32 %tmpload48, i32 %tmpload49)
-  %tmpload50 = load [10 x i32], [10 x i32]* %a
-  %tmpload51 = load i32, i32* %start
-  %tmpload52 = load i32, i32* %i
+  %tmpload50 = load [10 x i32], [10 x i32]* %a, align 4
+  %tmpload51 = load i32, i32* %start, align 4
+  %tmpload52 = load i32, i32* %i, align 4
   %tmpadd53 = add nsw i32 %tmpload52, %tmpload51
-  %tmpload54 = load i32, i32* %len
-  %tmpload55 = load i32, i32* %i
+  %tmpload54 = load i32, i32* %len, align 4
+  %tmpload55 = load i32, i32* %i, align 4
   %tmpsub56 = sub nsw i32 %tmpload54, %tmpload55
   call void @quicksort10([10 x i32] %tmpload50, i32 %tmpadd53, i32 %tmpsub56)
   ret void
@@ -136,29 +138,29 @@ lbl5:                                             ; preds = %lbl3
 
 define void @main() {
 entry:
-  %arr = alloca [10 x i32]
-  store [10 x i32] undef, [10 x i32]* %arr
+  %arr = alloca [10 x i32], align 4
+  store [10 x i32] undef, [10 x i32]* %arr, align 4
   %tmpgep = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 1
-  store i32 0, i32* %tmpgep
+  store i32 0, i32* %tmpgep, align 4
   %tmpgep1 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 2
-  store i32 1, i32* %tmpgep1
+  store i32 1, i32* %tmpgep1, align 4
   %tmpgep2 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 3
-  store i32 2, i32* %tmpgep2
+  store i32 2, i32* %tmpgep2, align 4
   %tmpgep3 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 4
-  store i32 3, i32* %tmpgep3
+  store i32 3, i32* %tmpgep3, align 4
   %tmpgep4 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 5
-  store i32 4, i32* %tmpgep4
+  store i32 4, i32* %tmpgep4, align 4
   %tmpgep5 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 6
-  store i32 5, i32* %tmpgep5
+  store i32 5, i32* %tmpgep5, align 4
   %tmpgep6 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 7
-  store i32 6, i32* %tmpgep6
+  store i32 6, i32* %tmpgep6, align 4
   %tmpgep7 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 8
-  store i32 7, i32* %tmpgep7
+  store i32 7, i32* %tmpgep7, align 4
   %tmpgep8 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 9
-  store i32 8, i32* %tmpgep8
+  store i32 8, i32* %tmpgep8, align 4
   %tmpgep9 = getelementptr [10 x i32], [10 x i32]* %arr, i32 0, i32 10
-  store i32 9, i32* %tmpgep9
-  %tmpload = load [10 x i32], [10 x i32]* %arr
+  store i32 9, i32* %tmpgep9, align 4
+  %tmpload = load [10 x i32], [10 x i32]* %arr, align 4
   call void @quicksort10([10 x i32] %tmpload, i32 0, i32 10)
   ret void
 }
@@ -0,0 +1,133 @@
+; ModuleID = 'elgin'
+source_filename = "examples/quicksort.eln"
+
+; Function Attrs: nofree nosync nounwind readnone
+define void @quicksort10([10 x i32] %0, i32 %1, i32 %2) local_unnamed_addr #0 {
+entry:
+  %a = alloca [10 x i32], align 4
+  %.fca.0.extract = extractvalue [10 x i32] %0, 0
+  %.fca.0.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 0
+  store i32 %.fca.0.extract, i32* %.fca.0.gep, align 4
+  %.fca.1.extract = extractvalue [10 x i32] %0, 1
+  %.fca.1.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 1
+  store i32 %.fca.1.extract, i32* %.fca.1.gep, align 4
+  %.fca.2.extract = extractvalue [10 x i32] %0, 2
+  %.fca.2.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 2
+  store i32 %.fca.2.extract, i32* %.fca.2.gep, align 4
+  %.fca.3.extract = extractvalue [10 x i32] %0, 3
+  %.fca.3.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 3
+  store i32 %.fca.3.extract, i32* %.fca.3.gep, align 4
+  %.fca.4.extract = extractvalue [10 x i32] %0, 4
+  %.fca.4.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 4
+  store i32 %.fca.4.extract, i32* %.fca.4.gep, align 4
+  %.fca.5.extract = extractvalue [10 x i32] %0, 5
+  %.fca.5.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 5
+  store i32 %.fca.5.extract, i32* %.fca.5.gep, align 4
+  %.fca.6.extract = extractvalue [10 x i32] %0, 6
+  %.fca.6.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 6
+  store i32 %.fca.6.extract, i32* %.fca.6.gep, align 4
+  %.fca.7.extract = extractvalue [10 x i32] %0, 7
+  %.fca.7.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 7
+  store i32 %.fca.7.extract, i32* %.fca.7.gep, align 4
+  %.fca.8.extract = extractvalue [10 x i32] %0, 8
+  %.fca.8.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 8
+  store i32 %.fca.8.extract, i32* %.fca.8.gep, align 4
+  %.fca.9.extract = extractvalue [10 x i32] %0, 9
+  %.fca.9.gep = getelementptr inbounds [10 x i32], [10 x i32]* %a, i64 0, i64 9
+  store i32 %.fca.9.extract, i32* %.fca.9.gep, align 4
+  %tmpcmp = icmp slt i32 %2, 2
+  br i1 %tmpcmp, label %common.ret, label %lbl2
+
+common.ret:                                       ; preds = %entry, %lbl5
+  ret void
+
+lbl2:                                             ; preds = %entry
+  %3 = sext i32 %1 to i64
+  %tmpgep = getelementptr [10 x i32], [10 x i32]* %a, i64 0, i64 %3
+  %tmpload3 = load i32, i32* %tmpgep, align 4
+  br label %lbl3
+
+lbl3:                                             ; preds = %lbl14, %lbl2
+  %tmpsub67.lcssa71 = phi i64 [ %indvars.iv73, %lbl14 ], [ 9, %lbl2 ]
+  %tmpadd1366.lcssa69 = phi i64 [ %indvars.iv, %lbl14 ], [ 0, %lbl2 ]
+  %sext = shl i64 %tmpadd1366.lcssa69, 32
+  %4 = ashr exact i64 %sext, 32
+  br label %lbl6
+
+lbl6:                                             ; preds = %lbl6, %lbl3
+  %indvars.iv = phi i64 [ %indvars.iv.next, %lbl6 ], [ %4, %lbl3 ]
+  %5 = add nsw i64 %indvars.iv, %3
+  %tmpgep8 = getelementptr [10 x i32], [10 x i32]* %a, i64 0, i64 %5
+  %tmpload9 = load i32, i32* %tmpgep8, align 4
+  %tmpcmp11 = icmp slt i32 %tmpload9, %tmpload3
+  %indvars.iv.next = add i64 %indvars.iv, 1
+  br i1 %tmpcmp11, label %lbl6, label %lbl9.preheader
+
+lbl9.preheader:                                   ; preds = %lbl6
+  %6 = trunc i64 %indvars.iv to i32
+  %tmpload14.fca.0.load = load i32, i32* %.fca.0.gep, align 4
+  %tmpload14.fca.1.load = load i32, i32* %.fca.1.gep, align 4
+  %tmpload14.fca.2.load = load i32, i32* %.fca.2.gep, align 4
+  %tmpload14.fca.3.load = load i32, i32* %.fca.3.gep, align 4
+  %tmpload14.fca.4.load = load i32, i32* %.fca.4.gep, align 4
+  %tmpload14.fca.5.load = load i32, i32* %.fca.5.gep, align 4
+  %tmpload14.fca.6.load = load i32, i32* %.fca.6.gep, align 4
+  %tmpload14.fca.7.load = load i32, i32* %.fca.7.gep, align 4
+  %tmpload14.fca.8.load = load i32, i32* %.fca.8.gep, align 4
+  %tmpload14.fca.9.load = load i32, i32* %.fca.9.gep, align 4
+  %sext76 = shl i64 %tmpsub67.lcssa71, 32
+  %7 = ashr exact i64 %sext76, 32
+  br label %lbl9
+
+lbl9:                                             ; preds = %lbl9, %lbl9.preheader
+  %indvars.iv73 = phi i64 [ %indvars.iv.next74, %lbl9 ], [ %7, %lbl9.preheader ]
+  %8 = add nsw i64 %indvars.iv73, %3
+  %tmpgep18 = getelementptr [10 x i32], [10 x i32]* %a, i64 0, i64 %8
+  %tmpload19 = load i32, i32* %tmpgep18, align 4
+  %tmpcmp21 = icmp slt i32 %tmpload19, %tmpload3
+  %indvars.iv.next74 = add i64 %indvars.iv73, -1
+  br i1 %tmpcmp21, label %lbl9, label %lbl11
+
+lbl11:                                            ; preds = %lbl9
+  %9 = trunc i64 %indvars.iv73 to i32
+  %tmpcmp25.not = icmp slt i32 %6, %9
+  br i1 %tmpcmp25.not, label %lbl14, label %lbl5
+
+lbl14:                                            ; preds = %lbl11
+  %10 = trunc i64 %5 to i32
+  %11 = trunc i64 %8 to i32
+  %12 = sext i32 %tmpload19 to i64
+  %tmpgep41 = getelementptr [10 x i32], [10 x i32]* %a, i64 0, i64 %12
+  store i32 %10, i32* %tmpgep41, align 4
+  %13 = sext i32 %tmpload9 to i64
+  %tmpgep46 = getelementptr [10 x i32], [10 x i32]* %a, i64 0, i64 %13
+  store i32 %11, i32* %tmpgep46, align 4
+  br label %lbl3
+
+lbl5:                                             ; preds = %lbl11
+  %14 = trunc i64 %indvars.iv to i32
+  %15 = trunc i64 %5 to i32
+  %tmpload14.fca.0.insert.le.le = insertvalue [10 x i32] poison, i32 %tmpload14.fca.0.load, 0
+  %tmpload14.fca.1.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.0.insert.le.le, i32 %tmpload14.fca.1.load, 1
+  %tmpload14.fca.2.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.1.insert.le.le, i32 %tmpload14.fca.2.load, 2
+  %tmpload14.fca.3.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.2.insert.le.le, i32 %tmpload14.fca.3.load, 3
+  %tmpload14.fca.4.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.3.insert.le.le, i32 %tmpload14.fca.4.load, 4
+  %tmpload14.fca.5.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.4.insert.le.le, i32 %tmpload14.fca.5.load, 5
+  %tmpload14.fca.6.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.5.insert.le.le, i32 %tmpload14.fca.6.load, 6
+  %tmpload14.fca.7.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.6.insert.le.le, i32 %tmpload14.fca.7.load, 7
+  %tmpload14.fca.8.insert.le.le = insertvalue [10 x i32] %tmpload14.fca.7.insert.le.le, i32 %tmpload14.fca.8.load, 8
+  %tmpload47.fca.9.insert = insertvalue [10 x i32] %tmpload14.fca.8.insert.le.le, i32 %tmpload14.fca.9.load, 9
+  tail call void @quicksort10([10 x i32] %tmpload47.fca.9.insert, i32 %1, i32 %14)
+  %tmpsub56 = sub nsw i32 %2, %14
+  tail call void @quicksort10([10 x i32] %tmpload47.fca.9.insert, i32 %15, i32 %tmpsub56)
+  br label %common.ret
+}
+
+; Function Attrs: nofree nosync nounwind readnone
+define void @main() local_unnamed_addr #0 {
+entry:
+  tail call void @quicksort10([10 x i32] [i32 undef, i32 0, i32 1, i32 2, i32 3, i32 4, i32 5, i32 6, i32 7, i32 8], i32 0, i32 10)
+  ret void
+}
+
+attributes #0 = { nofree nosync nounwind readnone }
@@ -0,0 +1,18 @@
+; ModuleID = 'elgin'
+source_filename = "examples/test.eln"
+
+declare i32 @puts(i8*)
+
+declare i64 @write(i32, i8*, i64)
+
+define i32 @main() {
+entry:
+  %x = alloca [10 x i32], align 4
+  store [10 x i32] undef, [10 x i32]* %x, align 4
+  %tmpgep = getelementptr [10 x i32], [10 x i32]* %x, i32 0, i32 10
+  store i32 3, i32* %tmpgep, align 4
+  %tmpload = load [10 x i32], [10 x i32]* %x, align 4
+  %tmpgep1 = getelementptr [10 x i32], [10 x i32]* %x, i32 0, i32 3
+  %tmpload2 = load i32, i32* %tmpgep1, align 4
+  ret i32 %tmpload2
+}
@@ -0,0 +1,10 @@
+; ModuleID = 'elgin'
+source_filename = "examples/test.eln"
+
+; Function Attrs: mustprogress nofree norecurse nosync nounwind readnone willreturn
+define i32 @main() local_unnamed_addr #0 {
+entry:
+  ret i32 undef
+}
+
+attributes #0 = { mustprogress nofree norecurse nosync nounwind readnone willreturn }
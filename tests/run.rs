@@ -0,0 +1,29 @@
+//! Checks the programs run in memory by `elgin run`
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn run(path: &str, args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_elgin")).arg("run").args(args).arg(path).output().unwrap();
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn exit_code() {
+    // the same as the executable from `elgin build`
+    assert_eq!(run("examples/factorial.eln", &[]), (120, String::new()));
+    assert_eq!(run("examples/factorial.eln", &["-O2"]), (120, String::new()));
+}
+
+#[test]
+fn stdout() {
+    assert_eq!(run("examples/hello.eln", &[]), (0, "Hello world!\n".to_owned()));
+
+    // puts resolves to the one in the elgin process
+    let path = env::temp_dir().join(format!("elgin-run-puts-{}.eln", std::process::id()));
+    fs::write(&path, "proc main(): i32 {\n    puts(\"hi\")\n    return 3\n}\n").unwrap();
+    let result = run(&path.to_string_lossy(), &[]);
+    let _ = fs::remove_file(&path);
+    assert_eq!(result, (3, "hi\n".to_owned()));
+}